@@ -0,0 +1,272 @@
+use crate::config::CONFIG;
+use crate::schema::{Column, Table};
+use alloy::dyn_abi::{DynSolType, DynSolValue, Specifier};
+use alloy::json_abi::{Event, JsonAbi};
+use alloy::primitives::{hex, Address, B256};
+use eyre::{eyre, Result, WrapErr};
+use serde::Deserialize;
+use std::{fs, path::Path, sync::OnceLock};
+
+/// Columns every ABI event table starts with, matching the hand-written event tables.
+const HEAD_COLUMNS: [(&str, &str, bool); 8] = [
+    ("chain_id", "UInt32", true),
+    ("block_number", "UInt64", true),
+    ("block_timestamp", "DateTime64(3, 'UTC')", false),
+    ("transaction_hash", "String", false),
+    ("transaction_index", "UInt32", true),
+    ("log_index", "UInt32", true),
+    ("log_address", "String", false),
+    ("event_id", "String", false),
+];
+
+/// One entry of the `INDEXER_ABI_CONFIG` file.
+#[derive(Debug, Deserialize)]
+struct SourceSpec {
+    name: String,
+    abi: String,
+    #[serde(default)]
+    addresses: Vec<Address>,
+    factory: Option<FactorySpec>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FactorySpec {
+    address: Address,
+    event: String,
+    argument: String,
+}
+
+/// A contract set indexed from a JSON ABI: fixed addresses and/or the contracts announced
+/// by a factory event.
+#[derive(Debug)]
+pub struct AbiSource {
+    pub name: String,
+    pub addresses: Vec<Address>,
+    pub factory: Option<Factory>,
+    pub events: Vec<AbiEvent>,
+}
+
+#[derive(Debug)]
+pub struct Factory {
+    pub address: Address,
+    pub event: Event,
+    /// Index of the event input holding the new contract's address.
+    pub argument: usize,
+}
+
+/// A non-anonymous ABI event and the table its logs are written to.
+#[derive(Debug)]
+pub struct AbiEvent {
+    pub table: Table,
+    pub event: Event,
+}
+
+static ABI_SOURCES: OnceLock<Vec<AbiSource>> = OnceLock::new();
+
+/// Loads the sources listed in `INDEXER_ABI_CONFIG`. Runs at startup, before the tables are
+/// created, so a broken config stops the indexer instead of leaving it without ABI tables.
+pub fn init() -> Result<()> {
+    let sources = match &CONFIG.abi_config {
+        Some(path) => load_sources(path).wrap_err_with(|| format!("loading ABI sources from {}", path))?,
+        None => Vec::new(),
+    };
+    let _ = ABI_SOURCES.set(sources);
+    Ok(())
+}
+
+/// The configured ABI sources, loaded by `init`.
+pub fn sources() -> &'static [AbiSource] {
+    ABI_SOURCES.get().expect("ABI sources are loaded at startup")
+}
+
+/// Tables of every configured ABI event.
+pub fn tables() -> Vec<Table> {
+    sources().iter().flat_map(|source| source.events.iter().map(|e| e.table.clone())).collect()
+}
+
+/// The source and event written to `table_name`, if it is an ABI event table.
+pub fn event_for_table(table_name: &str) -> Option<(&'static AbiSource, &'static AbiEvent)> {
+    sources().iter().find_map(|source| {
+        source.events.iter().find(|e| e.table.name == table_name).map(|event| (source, event))
+    })
+}
+
+/// Decodes a log emitted by one of the fixed addresses of a configured source, or by a
+/// contract discovered through the factories of the sources named in `discovered_by`,
+/// returning the matching event and its values in input order.
+pub fn decode_log(
+    address: Address,
+    discovered_by: &[&str],
+    topics: &[B256],
+    data: &[u8],
+) -> Option<(&'static Event, Vec<DynSolValue>)> {
+    use alloy::dyn_abi::EventExt;
+
+    let selector = topics.first()?;
+    let event = sources()
+        .iter()
+        .filter(|source| source.addresses.contains(&address) || discovered_by.contains(&source.name.as_str()))
+        .flat_map(|source| source.events.iter())
+        .map(|abi_event| &abi_event.event)
+        .find(|event| event.selector() == *selector)?;
+
+    let decoded = event.decode_log_parts(topics.iter().copied(), data).ok()?;
+    Some((event, decoded_inputs(event, decoded)))
+}
+
+/// A decoded value in the textual form its column is written with, matching
+/// `IntoClickhouseValue`; arrays and tuples are written as bracketed lists.
+pub fn format_value(value: &DynSolValue) -> String {
+    match value {
+        DynSolValue::Address(address) => address.to_checksum(Some(1)),
+        DynSolValue::Bool(b) => if *b { "1" } else { "0" }.to_string(),
+        DynSolValue::Uint(u, _) => u.to_string(),
+        DynSolValue::Int(i, _) => i.to_string(),
+        DynSolValue::FixedBytes(word, size) => hex::encode_prefixed(&word[..*size]),
+        DynSolValue::Bytes(bytes) => hex::encode_prefixed(bytes),
+        DynSolValue::String(s) => s.clone(),
+        DynSolValue::Function(f) => hex::encode_prefixed(f.as_slice()),
+        DynSolValue::Array(values) | DynSolValue::FixedArray(values) | DynSolValue::Tuple(values) => {
+            format!("[{}]", values.iter().map(format_value).collect::<Vec<_>>().join(","))
+        }
+    }
+}
+
+fn load_sources(path: &str) -> Result<Vec<AbiSource>> {
+    let base = Path::new(path).parent().unwrap_or_else(|| Path::new("."));
+    let specs: Vec<SourceSpec> = serde_json::from_str(&fs::read_to_string(path)?)
+        .wrap_err("invalid ABI config")?;
+
+    specs.into_iter().map(|spec| load_source(base, spec)).collect()
+}
+
+fn load_source(base: &Path, spec: SourceSpec) -> Result<AbiSource> {
+    let contents = fs::read_to_string(base.join(&spec.abi)).wrap_err_with(|| format!("reading ABI {}", spec.abi))?;
+    // Accept a bare ABI array as well as a compiler artifact with an `abi` field.
+    let abi: JsonAbi = match serde_json::from_str::<serde_json::Value>(&contents)? {
+        serde_json::Value::Object(mut artifact) => serde_json::from_value(artifact.remove("abi").unwrap_or_default())?,
+        value => serde_json::from_value(value)?,
+    };
+
+    let factory = spec
+        .factory
+        .map(|factory| -> Result<Factory> {
+            let event = Event::parse(&factory.event).map_err(|e| eyre!("invalid factory event {}: {}", factory.event, e))?;
+            let argument = event
+                .inputs
+                .iter()
+                .position(|input| input.name == factory.argument && input.ty == "address")
+                .ok_or_else(|| eyre!("factory event has no address input {}", factory.argument))?;
+            Ok(Factory { address: factory.address, event, argument })
+        })
+        .transpose()?;
+
+    let source_name = snake_case(&spec.name);
+    let mut events: Vec<AbiEvent> = Vec::new();
+    for event in abi.events().filter(|event| !event.anonymous) {
+        let mut table_name = format!("abi_{}_{}", source_name, snake_case(&event.name));
+        if events.iter().any(|e| e.table.name == table_name) {
+            table_name = format!("{}_{}", table_name, hex::encode(&event.selector()[..4]));
+        }
+        events.push(AbiEvent { table: event_table(table_name, event)?, event: event.clone() });
+    }
+
+    Ok(AbiSource { name: source_name, addresses: spec.addresses, factory, events })
+}
+
+fn event_table(name: String, event: &Event) -> Result<Table> {
+    let mut columns: Vec<Column> = HEAD_COLUMNS
+        .iter()
+        .map(|&(name, sql_type, primary_key)| Column { name, sql_type, nullable: false, primary_key })
+        .collect();
+
+    for (i, input) in event.inputs.iter().enumerate() {
+        let mut column_name = snake_case(&input.name);
+        if column_name.is_empty() { column_name = format!("arg{}", i); }
+        if columns.iter().any(|c| c.name == column_name) { column_name = format!("arg_{}", column_name); }
+
+        let sql_type = match input.resolve()? {
+            DynSolType::Bool => "Bool",
+            DynSolType::Uint(bits) if bits <= 64 => "UInt64",
+            DynSolType::Int(bits) if bits <= 64 => "Int64",
+            DynSolType::Uint(_) => "UInt256",
+            DynSolType::Int(_) => "Int256",
+            _ => "String",
+        };
+        columns.push(Column { name: leak(column_name), sql_type, nullable: false, primary_key: false });
+    }
+
+    Ok(Table {
+        name: leak(name),
+        columns,
+        indexes: vec![],
+        partition_by: Some("toDate(block_timestamp)"),
+    })
+}
+
+/// Table and column names live for the whole process, like the hand-written definitions.
+fn leak(name: String) -> &'static str {
+    Box::leak(name.into_boxed_str())
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if previous_lower { out.push('_'); }
+            out.push(c.to_ascii_lowercase());
+            previous_lower = false;
+        } else if c.is_ascii_alphanumeric() {
+            out.push(c);
+            previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        } else {
+            if !out.ends_with('_') && !out.is_empty() { out.push('_'); }
+            previous_lower = false;
+        }
+    }
+    out.trim_matches('_').to_string()
+}
+
+impl AbiSource {
+    /// Address of the contract announced by a factory log, if the log is one.
+    pub fn discovered_contract(&self, log_address: Address, topics: &[B256], data: &[u8]) -> Option<Address> {
+        use alloy::dyn_abi::EventExt;
+
+        let factory = self.factory.as_ref()?;
+        if log_address != factory.address || topics.first() != Some(&factory.event.selector()) { return None; }
+
+        let decoded = factory.event.decode_log_parts(topics.iter().copied(), data).ok()?;
+        match decoded_inputs(&factory.event, decoded).into_iter().nth(factory.argument)? {
+            DynSolValue::Address(address) => Some(address),
+            _ => None,
+        }
+    }
+}
+
+/// Indexed and body values of a decoded event merged back into input order.
+pub fn decoded_inputs(event: &Event, decoded: alloy::dyn_abi::DecodedEvent) -> Vec<DynSolValue> {
+    let mut indexed = decoded.indexed.into_iter();
+    let mut body = decoded.body.into_iter();
+    event
+        .inputs
+        .iter()
+        .filter_map(|input| if input.indexed { indexed.next() } else { body.next() })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snake_case_names() {
+        assert_eq!(snake_case("OwnershipTransferred"), "ownership_transferred");
+        assert_eq!(snake_case("sqrtPriceX96"), "sqrt_price_x96");
+        assert_eq!(snake_case("tokenID"), "token_id");
+        assert_eq!(snake_case("ERC20Token"), "erc20_token");
+        assert_eq!(snake_case("Uniswap V4"), "uniswap_v4");
+        assert_eq!(snake_case("__value__"), "value");
+        assert_eq!(snake_case("_amount0"), "amount0");
+    }
+}
@@ -0,0 +1,135 @@
+use crate::indexer::Indexer;
+use crate::storage::init_tables;
+use crate::utils::connect_to_clickhouse;
+use clap::{Args, CommandFactory, FromArgMatches, Subcommand};
+use eyre::Result;
+use reth_cli_commands::common::{AccessRights, EnvironmentArgs};
+use reth_ethereum::{cli::{chainspec::EthereumChainSpecParser, Cli}, node::EthereumNode};
+use reth_provider::{BlockNumReader, BlockReader, PruneCheckpointReader, ReceiptProvider};
+use reth_prune_types::PruneSegment;
+use reth_tracing::{RethTracer, Tracer};
+use std::sync::Arc;
+
+/// The node's command line: reth's `Cli` with the indexer's commands added to its
+/// subcommands, so they are listed in its `--help` and take its global flags.
+#[derive(Debug)]
+pub enum IndexerCli {
+    /// One of reth's commands, run through `Cli::run`.
+    Reth(Cli),
+    /// One of the indexer's commands, run without starting the node.
+    Indexer(IndexerCommand),
+}
+
+/// Commands of the indexer itself, run without starting the node.
+#[derive(Debug, Subcommand)]
+pub enum IndexerCommand {
+    /// Rebuild derived tables for a block range from the raw PoolManager log archive
+    Reindex(ReindexArgs),
+    /// Revert and re-process a block range with blocks and receipts from the node database
+    Reprocess(ReprocessArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ReindexArgs {
+    /// First block to rebuild
+    #[arg(long)]
+    from: u64,
+    /// Last block to rebuild, inclusive
+    #[arg(long)]
+    to: u64,
+    /// Comma-separated table or processor names; every processor that can be rebuilt when omitted
+    #[arg(long, value_delimiter = ',')]
+    processors: Vec<String>,
+    /// Only report the rows that would be deleted and rebuilt
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ReprocessArgs {
+    #[command(flatten)]
+    env: EnvironmentArgs<EthereumChainSpecParser>,
+    #[command(flatten)]
+    range: ReindexArgs,
+}
+
+impl IndexerCli {
+    /// reth's `Cli` command with the indexer's commands added.
+    pub fn command() -> clap::Command {
+        IndexerCommand::augment_subcommands(<Cli as CommandFactory>::command())
+    }
+
+    /// Parses the process arguments, exiting with clap's message when they are invalid.
+    pub fn parse_args() -> Self {
+        let matches = Self::command().get_matches();
+        let parsed = match matches.subcommand_name() {
+            Some(name) if IndexerCommand::has_subcommand(name) => IndexerCommand::from_arg_matches(&matches).map(Self::Indexer),
+            _ => <Cli as FromArgMatches>::from_arg_matches(&matches).map(Self::Reth),
+        };
+        parsed.unwrap_or_else(|e| e.format(&mut Self::command()).exit())
+    }
+}
+
+impl IndexerCommand {
+    pub fn run(self) -> Result<()> {
+        let _guard = RethTracer::new().init()?;
+        tokio::runtime::Runtime::new()?.block_on(async move {
+            match self {
+                IndexerCommand::Reindex(args) => args.execute().await,
+                IndexerCommand::Reprocess(args) => args.execute().await,
+            }
+        })
+    }
+}
+
+impl ReindexArgs {
+    async fn execute(self) -> Result<()> {
+        eyre::ensure!(self.from <= self.to, "--from must not be greater than --to");
+
+        let client = Arc::new(connect_to_clickhouse().await?);
+        init_tables(&client).await?;
+
+        Indexer::new().reindex_from_archive(&client, self.from, self.to, &self.processors, self.dry_run).await
+    }
+}
+
+impl ReprocessArgs {
+    /// Opens the node database read-only, so it can run next to the node, and refuses ranges
+    /// whose receipts or transactions the node has pruned.
+    async fn execute(self) -> Result<()> {
+        let range = self.range;
+        eyre::ensure!(range.from <= range.to, "--from must not be greater than --to");
+
+        let provider_factory = self.env.init::<EthereumNode>(AccessRights::RO)?.provider_factory;
+        let provider = provider_factory.provider()?;
+        let last_block = provider.last_block_number()?;
+        eyre::ensure!(range.to <= last_block, "--to is past the last block in the node database ({})", last_block);
+        for segment in [PruneSegment::Receipts, PruneSegment::ContractLogs, PruneSegment::Transactions] {
+            let pruned = provider.get_prune_checkpoint(segment)?.and_then(|checkpoint| checkpoint.block_number);
+            if let Some(pruned) = pruned.filter(|pruned| *pruned >= range.from) {
+                eyre::bail!("{:?} are pruned up to block {} in the node database", segment, pruned);
+            }
+        }
+        drop(provider);
+
+        let client = Arc::new(connect_to_clickhouse().await?);
+        init_tables(&client).await?;
+
+        Indexer::new()
+            .reindex_from_node(&client, range.from, range.to, &range.processors, range.dry_run, |start, end| {
+                let provider = provider_factory.provider()?;
+                let blocks = provider.recovered_block_range(start..=end)?;
+                let receipts = provider.receipts_by_block_range(start..=end)?;
+                eyre::ensure!(
+                    blocks.len() as u64 == end - start + 1 && receipts.len() == blocks.len(),
+                    "the node database returned {} blocks and {} receipt lists for blocks {}..={}",
+                    blocks.len(),
+                    receipts.len(),
+                    start,
+                    end
+                );
+                Ok(blocks.into_iter().zip(receipts).collect())
+            })
+            .await
+    }
+}
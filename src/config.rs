@@ -0,0 +1,90 @@
+use alloy::primitives::{address, Address, FixedBytes};
+use std::env;
+
+const DEFAULT_STABLECOINS: [Address; 3] = [
+    address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"), // USDC
+    address!("0xdAC17F958D2ee523a2206206994597C13D831ec7"), // USDT
+    address!("0x6B175474E89094C44Da98b954EedeAC495271d0F"), // DAI
+];
+const WETH: Address = address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+const DEFAULT_ROUTERS: [Address; 4] = [
+    address!("0x66a9893cc07d91d95644aedd05d03f95e1dba8af"), // Universal Router (v4)
+    address!("0x3fC91A3afd70395Cd496C647d5a6CC9D4B2b7FAD"), // Universal Router
+    address!("0x68b3465833fb72A70ecDF485E0e4C7bD8665Fc45"), // SwapRouter02
+    address!("0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D"), // UniswapV2Router02
+];
+const DEFAULT_AGGREGATORS: [Address; 5] = [
+    address!("0x111111125421cA6dc452d289314280a0f8842A65"), // 1inch v6
+    address!("0xDef1C0ded9bec7F1a1670819833240f027b25EfF"), // 0x Exchange Proxy
+    address!("0x9008D19f58AAbD9eD0D60971565AA8510560ab41"), // CoW Protocol settlement
+    address!("0x6131B5fae19EA4f9D964eAc0408E4408b66337b5"), // KyberSwap
+    address!("0xCf5540fFFCdC3d510B18bFcA6d2b9987b0772559"), // Odos v2
+];
+
+/// Indexer settings read from the environment at startup.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Replay PoolManager transactions for processors that read call traces
+    /// (`INDEXER_TRACING`, default `true`).
+    pub tracing_enabled: bool,
+    /// ETH/stablecoin v4 pools whose latest swap price is used as the ETH/USD price
+    /// (`INDEXER_USD_REFERENCE_POOLS`, comma-separated pool ids).
+    pub usd_reference_pools: Vec<FixedBytes<32>>,
+    /// Tokens valued at one dollar (`INDEXER_USD_STABLECOINS`, default USDC, USDT, DAI).
+    pub stablecoins: Vec<Address>,
+    /// Tokens valued at the ETH/USD price: the native currency and WETH.
+    pub eth_currencies: Vec<Address>,
+    /// Router contracts calling the PoolManager on behalf of users
+    /// (`INDEXER_ROUTERS`, default the Universal Routers).
+    pub routers: Vec<Address>,
+    /// Aggregator contracts routing through v4 among other venues
+    /// (`INDEXER_AGGREGATORS`, default 1inch, 0x, CoW Protocol, KyberSwap, Odos).
+    pub aggregators: Vec<Address>,
+    /// Index Uniswap v3 pools, swaps, liquidity changes and collects
+    /// (`INDEXER_UNISWAP_V3`, default `false`).
+    pub v3_enabled: bool,
+    /// Index Uniswap v2 pairs, swaps and syncs (`INDEXER_UNISWAP_V2`, default `false`).
+    pub v2_enabled: bool,
+    /// JSON file listing contracts to index from their ABI, one table per event
+    /// (`INDEXER_ABI_CONFIG`, unset by default).
+    pub abi_config: Option<String>,
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        Self {
+            tracing_enabled: env_flag("INDEXER_TRACING", true),
+            usd_reference_pools: env_list("INDEXER_USD_REFERENCE_POOLS").unwrap_or_default(),
+            stablecoins: env_list("INDEXER_USD_STABLECOINS").unwrap_or_else(|| DEFAULT_STABLECOINS.to_vec()),
+            eth_currencies: vec![Address::ZERO, WETH],
+            routers: env_list("INDEXER_ROUTERS").unwrap_or_else(|| DEFAULT_ROUTERS.to_vec()),
+            aggregators: env_list("INDEXER_AGGREGATORS").unwrap_or_else(|| DEFAULT_AGGREGATORS.to_vec()),
+            v3_enabled: env_flag("INDEXER_UNISWAP_V3", false),
+            v2_enabled: env_flag("INDEXER_UNISWAP_V2", false),
+            abi_config: env::var("INDEXER_ABI_CONFIG").ok().filter(|path| !path.trim().is_empty()),
+        }
+    }
+}
+
+fn env_flag(name: &str, default: bool) -> bool {
+    match env::var(name) {
+        Ok(value) => matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"),
+        Err(_) => default,
+    }
+}
+
+fn env_list<T: std::str::FromStr>(name: &str) -> Option<Vec<T>> {
+    let value = env::var(name).ok()?;
+    Some(
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .filter_map(|item| item.parse().ok())
+            .collect(),
+    )
+}
+
+lazy_static::lazy_static! {
+    pub static ref CONFIG: Config = Config::from_env();
+}
@@ -1,22 +1,57 @@
+use crate::config::CONFIG;
 use crate::schema::get as get_table;
+use crate::storage::archive::{archived_blocks, check_coverage as check_archive_coverage};
 use crate::storage::writer::ClickhouseWriter as DbWriter;
 use crate::processors::pools::process_uni_v4_pools;
 use crate::processors::swaps::process_uni_v4_swaps;
 use crate::processors::modify_liquidity::process_uni_v4_modify_liquidity;
 use crate::processors::donations::process_uni_v4_donations;
+use crate::processors::ticks::process_uni_v4_ticks;
+use crate::processors::positions::process_uni_v4_positions;
+use crate::processors::position_nfts::process_uni_v4_position_nfts;
+use crate::processors::hooks::process_uni_v4_hooks;
+use crate::processors::hook_calls::process_uni_v4_hook_calls;
+use crate::processors::claims::{
+    process_uni_v4_claims_transfers,
+    process_uni_v4_claims_approvals,
+    process_uni_v4_claims_balances,
+};
+use crate::processors::protocol_fees::{
+    process_uni_v4_protocol_fees,
+    process_uni_v4_protocol_fee_controllers,
+};
+use crate::processors::fee_changes::process_uni_v4_fee_changes;
+use crate::processors::tokens::{process_tokens, ContractCaller};
+use crate::processors::swaps_enriched::process_uni_v4_swaps_enriched;
+use crate::processors::fees::{process_uni_v4_fees, process_uni_v4_position_fees};
+use crate::processors::sandwiches::process_uni_v4_sandwiches;
+use crate::processors::jit_liquidity::process_uni_v4_jit_liquidity;
+use crate::processors::transactions::process_uni_v4_transactions;
+use crate::processors::routes::process_uni_v4_routes;
+use crate::processors::settlements::process_uni_v4_settlements;
+use crate::processors::hook_logs::process_uni_v4_hook_logs;
+use crate::processors::raw_logs::{process_uni_v4_raw_logs, process_uni_v4_decode_failures};
+use crate::processors::v3::{
+    process_uni_v3_pools,
+    process_uni_v3_swaps,
+    process_uni_v3_modify_liquidity,
+    process_uni_v3_collects,
+};
+use crate::processors::v2::{process_uni_v2_pools, process_uni_v2_swaps, process_uni_v2_syncs};
+use crate::processors::abi_events::{process_abi_events, process_abi_contracts};
+use crate::abi::{event_for_table, sources as abi_sources};
+use alloy::primitives::{address, Address};
+use alloy_consensus::Transaction;
 use alloy_rpc_types::{BlockId, BlockNumberOrTag};
 use alloy_rpc_types_trace::parity::{TraceResultsWithTransactionHash, TraceType};
 use eyre::Result;
-use reth_ethereum::{
-    node::api::FullNodeComponents,
-    rpc::api::eth::helpers::FullEthApi,
-};
+use reth_ethereum::rpc::api::eth::helpers::FullEthApi;
 use reth_rpc_eth_api::EthApiTypes;
 use reth_rpc_convert::RpcTypes;
 use alloy_network::{Network, TransactionBuilder};
 use reth_primitives::{RecoveredBlock, Block, Receipt};
 use reth_tracing::tracing::{info, warn};
-use std::{sync::Arc, time::Instant, collections::HashSet};
+use std::{sync::Arc, time::{Duration, Instant}, collections::HashSet, ops::RangeInclusive};
 use reth_rpc::TraceApi;
 use clickhouse::Client;
 
@@ -24,53 +59,91 @@ pub type EthereumBlock = RecoveredBlock<Block>;
 pub type EthereumReceipts = Vec<Receipt>;
 pub type EthereumBlockData = (EthereumBlock, EthereumReceipts);
 
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+/// Blocks read from the raw log archive per query when reindexing.
+const ARCHIVE_BATCH_BLOCKS: u64 = 1_000;
+/// Blocks read from the node database at once when reindexing, with all their receipts.
+const NODE_BATCH_BLOCKS: u64 = 100;
+/// Times a block is traced before the processors reading call traces fail for it.
+const TRACE_ATTEMPTS: u32 = 3;
+
 #[derive(Clone)]
-pub struct ProcessingComponents<Node: FullNodeComponents, EthApi: FullEthApi> {
-    pub eth_api: Arc<EthApi>,
-    pub block_traces: Option<Vec<TraceResultsWithTransactionHash>>,
-    pub provider: Node::Provider,
+pub struct ProcessingComponents {
+    /// The node's `eth_call`, `None` when blocks are replayed from the raw log archive.
+    pub eth_api: Option<Arc<dyn ContractCaller>>,
+    pub block_traces: Option<Arc<Vec<TraceResultsWithTransactionHash>>>,
     pub client: Arc<Client>,
 }
 
-struct ProcessorInfo<Node: FullNodeComponents, EthApi: FullEthApi> {
+#[derive(Clone)]
+struct ProcessorInfo {
     table_name: &'static str,
     processor_name: &'static str,
+    requires_traces: bool,
+    requires_calls: bool,
+    replayable: bool,
+    carries_state: bool,
     processor: for<'a> fn(
         &'a EthereumBlockData,
-        ProcessingComponents<Node, EthApi>,
+        ProcessingComponents,
         &'a mut DbWriter
     ) -> futures::future::BoxFuture<'a, Result<()>>,
 }
 
-impl<Node: FullNodeComponents, EthApi: FullEthApi> ProcessorInfo<Node, EthApi> {
+impl ProcessorInfo {
     fn new(
         table_name: &'static str,
         processor_name: &'static str,
         processor: for<'a> fn(
             &'a EthereumBlockData,
-            ProcessingComponents<Node, EthApi>,
+            ProcessingComponents,
             &'a mut DbWriter
         ) -> futures::future::BoxFuture<'a, Result<()>>,
     ) -> Self {
         Self {
             table_name,
             processor_name,
+            requires_traces: false,
+            requires_calls: false,
+            replayable: false,
+            carries_state: false,
             processor,
         }
     }
+
+    /// Marks the processor as reading `ProcessingComponents::block_traces`.
+    fn with_traces(mut self) -> Self {
+        self.requires_traces = true;
+        self
+    }
+
+    /// Marks the processor as needing `ProcessingComponents::eth_api` to write its rows.
+    fn with_calls(mut self) -> Self {
+        self.requires_calls = true;
+        self
+    }
+
+    /// Marks the processor as only reading PoolManager logs and transaction fields kept in
+    /// `uni_v4_raw_logs` and `uni_v4_transactions`, so its table can be rebuilt from them.
+    fn replayable(mut self) -> Self {
+        self.replayable = true;
+        self
+    }
+
+    /// Marks the processor as starting from its own rows of earlier blocks, so rows it wrote
+    /// after a reindexed range were derived from the rows being replaced.
+    fn stateful(mut self) -> Self {
+        self.carries_state = true;
+        self
+    }
 }
 
-pub struct Indexer<Node: FullNodeComponents, EthApi: FullEthApi> {
-    processors: Vec<ProcessorInfo<Node, EthApi>>,
+pub struct Indexer {
+    processors: Vec<ProcessorInfo>,
 }
 
-impl<Node: FullNodeComponents, EthApi: FullEthApi> Indexer<Node, EthApi> {
-    pub fn new() -> Self
-    where
-        EthApi: EthApiTypes,
-        <EthApi as EthApiTypes>::NetworkTypes: RpcTypes + Network,
-        <<EthApi as EthApiTypes>::NetworkTypes as RpcTypes>::TransactionRequest: Default + TransactionBuilder<<EthApi as EthApiTypes>::NetworkTypes>,
-    {
+impl Indexer {
+    pub fn new() -> Self {
         let mut indexer = Self {
             processors: Vec::new(),
         };
@@ -79,37 +152,233 @@ impl<Node: FullNodeComponents, EthApi: FullEthApi> Indexer<Node, EthApi> {
         indexer.add_processor("uni_v4_swaps", "Swaps");
         indexer.add_processor("uni_v4_modify_liquidity", "ModifyLiquidity");
         indexer.add_processor("uni_v4_donations", "Donations");
+        indexer.add_processor("uni_v4_ticks", "Ticks");
+        indexer.add_processor("uni_v4_positions", "Positions");
+        indexer.add_processor("uni_v4_position_nfts", "PositionNfts");
+        indexer.add_processor("uni_v4_hooks", "Hooks");
+        indexer.add_processor("uni_v4_hook_calls", "HookCalls");
+        indexer.add_processor("uni_v4_claims_transfers", "ClaimsTransfers");
+        indexer.add_processor("uni_v4_claims_approvals", "ClaimsApprovals");
+        indexer.add_processor("uni_v4_claims_balances", "ClaimsBalances");
+        indexer.add_processor("uni_v4_protocol_fees", "ProtocolFees");
+        indexer.add_processor("uni_v4_protocol_fee_controllers", "ProtocolFeeControllers");
+        indexer.add_processor("uni_v4_fee_changes", "FeeChanges");
+        indexer.add_processor("tokens", "Tokens");
+        indexer.add_processor("uni_v4_swaps_enriched", "SwapsEnriched");
+        indexer.add_processor("uni_v4_fees", "Fees");
+        indexer.add_processor("uni_v4_position_fees", "PositionFees");
+        indexer.add_processor("uni_v4_sandwiches", "Sandwiches");
+        indexer.add_processor("uni_v4_jit_liquidity", "JitLiquidity");
+        indexer.add_processor("uni_v4_transactions", "Transactions");
+        indexer.add_processor("uni_v4_routes", "Routes");
+        indexer.add_processor("uni_v4_settlements", "Settlements");
+        indexer.add_processor("uni_v4_hook_logs", "HookLogs");
+        indexer.add_processor("uni_v4_raw_logs", "RawLogs");
+        indexer.add_processor("uni_v4_decode_failures", "DecodeFailures");
+
+        if CONFIG.v3_enabled {
+            indexer.add_processor("uni_v3_pools", "V3Pools");
+            indexer.add_processor("uni_v3_swaps", "V3Swaps");
+            indexer.add_processor("uni_v3_modify_liquidity", "V3ModifyLiquidity");
+            indexer.add_processor("uni_v3_collects", "V3Collects");
+        }
+        if CONFIG.v2_enabled {
+            indexer.add_processor("uni_v2_pools", "V2Pools");
+            indexer.add_processor("uni_v2_swaps", "V2Swaps");
+            indexer.add_processor("uni_v2_syncs", "V2Syncs");
+        }
+        if abi_sources().iter().any(|source| source.factory.is_some()) {
+            indexer.add_processor("abi_contracts", "AbiContracts");
+        }
+        for event in abi_sources().iter().flat_map(|source| source.events.iter()) {
+            indexer.add_processor(event.table.name, event.table.name);
+        }
 
         info!("Initialized indexer with processors: {:?}", indexer.list_processors());
         indexer
     }
 
-    pub fn add_processor(&mut self, table_name: &'static str, processor_name: &'static str)
-    where
-        EthApi: EthApiTypes,
-        <EthApi as EthApiTypes>::NetworkTypes: RpcTypes + Network,
-        <<EthApi as EthApiTypes>::NetworkTypes as RpcTypes>::TransactionRequest: Default + TransactionBuilder<<EthApi as EthApiTypes>::NetworkTypes>,
-    {
+    pub fn add_processor(&mut self, table_name: &'static str, processor_name: &'static str) {
         let processor = match table_name {
             "uni_v4_pools" => ProcessorInfo::new(
                 table_name,
                 processor_name,
-                |block_data, components, writer| Box::pin(process_uni_v4_pools::<Node, EthApi>(block_data, components, writer))
-            ),
+                |block_data, components, writer| Box::pin(process_uni_v4_pools(block_data, components, writer))
+            ).replayable(),
             "uni_v4_swaps" => ProcessorInfo::new(
                 table_name,
                 processor_name,
-                |block_data, components, writer| Box::pin(process_uni_v4_swaps::<Node, EthApi>(block_data, components, writer))
-            ),
+                |block_data, components, writer| Box::pin(process_uni_v4_swaps(block_data, components, writer))
+            ).with_traces(),
             "uni_v4_modify_liquidity" => ProcessorInfo::new(
                 table_name,
                 processor_name,
-                |block_data, components, writer| Box::pin(process_uni_v4_modify_liquidity::<Node, EthApi>(block_data, components, writer))
-            ),
+                |block_data, components, writer| Box::pin(process_uni_v4_modify_liquidity(block_data, components, writer))
+            ).replayable(),
             "uni_v4_donations" => ProcessorInfo::new(
                 table_name,
                 processor_name,
-                |block_data, components, writer| Box::pin(process_uni_v4_donations::<Node, EthApi>(block_data, components, writer))
+                |block_data, components, writer| Box::pin(process_uni_v4_donations(block_data, components, writer))
+            ).replayable(),
+            "uni_v4_ticks" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_ticks(block_data, components, writer))
+            ).replayable().stateful(),
+            "uni_v4_positions" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_positions(block_data, components, writer))
+            ).replayable().stateful(),
+            "uni_v4_position_nfts" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_position_nfts(block_data, components, writer))
+            ).stateful(),
+            "uni_v4_hooks" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_hooks(block_data, components, writer))
+            ).replayable().stateful(),
+            "uni_v4_hook_calls" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_hook_calls(block_data, components, writer))
+            ).with_traces(),
+            "uni_v4_claims_transfers" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_claims_transfers(block_data, components, writer))
+            ).replayable(),
+            "uni_v4_claims_approvals" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_claims_approvals(block_data, components, writer))
+            ).replayable(),
+            "uni_v4_claims_balances" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_claims_balances(block_data, components, writer))
+            ).replayable().stateful(),
+            "uni_v4_protocol_fees" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_protocol_fees(block_data, components, writer))
+            ).replayable(),
+            "uni_v4_protocol_fee_controllers" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_protocol_fee_controllers(block_data, components, writer))
+            ).replayable(),
+            "uni_v4_fee_changes" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_fee_changes(block_data, components, writer))
+            ).replayable().stateful(),
+            "tokens" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_tokens(block_data, components, writer))
+            ).with_calls(),
+            "uni_v4_swaps_enriched" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_swaps_enriched(block_data, components, writer))
+            ).replayable(),
+            "uni_v4_fees" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_fees(block_data, components, writer))
+            ).replayable().stateful(),
+            "uni_v4_position_fees" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_position_fees(block_data, components, writer))
+            ).replayable().stateful(),
+            "uni_v4_sandwiches" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_sandwiches(block_data, components, writer))
+            ).replayable(),
+            "uni_v4_jit_liquidity" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_jit_liquidity(block_data, components, writer))
+            ).replayable(),
+            "uni_v4_transactions" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_transactions(block_data, components, writer))
+            ),
+            "uni_v4_routes" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_routes(block_data, components, writer))
+            ).with_traces(),
+            "uni_v4_settlements" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_settlements(block_data, components, writer))
+            ).with_traces(),
+            "uni_v4_hook_logs" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_hook_logs(block_data, components, writer))
+            ),
+            "uni_v4_raw_logs" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_raw_logs(block_data, components, writer))
+            ),
+            "uni_v4_decode_failures" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v4_decode_failures(block_data, components, writer))
+            ).replayable(),
+            "uni_v3_pools" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v3_pools(block_data, components, writer))
+            ),
+            "uni_v3_swaps" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v3_swaps(block_data, components, writer))
+            ).with_calls(),
+            "uni_v3_modify_liquidity" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v3_modify_liquidity(block_data, components, writer))
+            ).with_calls(),
+            "uni_v3_collects" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v3_collects(block_data, components, writer))
+            ).with_calls(),
+            "uni_v2_pools" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v2_pools(block_data, components, writer))
+            ),
+            "uni_v2_swaps" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v2_swaps(block_data, components, writer))
+            ).with_calls(),
+            "uni_v2_syncs" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_uni_v2_syncs(block_data, components, writer))
+            ).with_calls(),
+            "abi_contracts" => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_abi_contracts(block_data, components, writer))
+            ),
+            name if event_for_table(name).is_some() => ProcessorInfo::new(
+                table_name,
+                processor_name,
+                |block_data, components, writer| Box::pin(process_abi_events(block_data, components, writer))
             ),
             _ => return,
         };
@@ -134,63 +403,241 @@ impl<Node: FullNodeComponents, EthApi: FullEthApi> Indexer<Node, EthApi> {
         Ok(())
     }
 
-    pub async fn process_blocks(
+    /// Rebuilds the tables of the replayable processors, or of those among them named in
+    /// `only` (by table or processor name), for blocks `from..=to` from the raw log archive.
+    /// Nothing is deleted unless the archive covers the whole range, and each batch of rows
+    /// is only deleted once its archived blocks have been read.
+    pub async fn reindex_from_archive(
+        &self,
+        client: &Arc<Client>,
+        from: u64,
+        to: u64,
+        only: &[String],
+        dry_run: bool,
+    ) -> Result<()> {
+        let indexer = self.select(only, |p| p.replayable, "cannot be rebuilt from the raw log archive");
+        if indexer.processors.is_empty() { return Ok(()); }
+
+        let tables: Vec<&str> = indexer.processors.iter().map(|p| p.table_name).collect();
+        let coverage = check_archive_coverage(client, from, to, &tables).await?;
+        let source = format!("the raw log archive ({} logs in {} blocks)", coverage.logs, coverage.blocks);
+        indexer
+            .reindex(client, from..=to, dry_run, &source, ARCHIVE_BATCH_BLOCKS, |start, end| {
+                archived_blocks(client, start, end)
+            })
+            .await
+    }
+
+    /// Same as `reindex_from_archive` with blocks and receipts read by `load` from the node
+    /// database, so every processor can be rebuilt except those needing `eth_call` or call
+    /// traces, which only the running node provides.
+    pub async fn reindex_from_node<F>(
+        &self,
+        client: &Arc<Client>,
+        from: u64,
+        to: u64,
+        only: &[String],
+        dry_run: bool,
+        mut load: F,
+    ) -> Result<()>
+    where
+        F: FnMut(u64, u64) -> Result<Vec<EthereumBlockData>>,
+    {
+        let indexer = self.select(
+            only,
+            |p| !p.requires_calls && !p.requires_traces,
+            "needs eth_call or call traces from the running node",
+        );
+        if indexer.processors.is_empty() { return Ok(()); }
+
+        let source = format!("the node database ({} blocks)", (to - from).saturating_add(1));
+        indexer
+            .reindex(client, from..=to, dry_run, &source, NODE_BATCH_BLOCKS, |start, end| {
+                std::future::ready(load(start, end))
+            })
+            .await
+    }
+
+    /// The processors that do not read call traces, run on blocks that could not be traced.
+    fn without_traces(&self) -> Self {
+        Self { processors: self.processors.iter().filter(|p| !p.requires_traces).cloned().collect() }
+    }
+
+    /// The processors named in `only`, or all of them when it is empty, that are `runnable`.
+    fn select(&self, only: &[String], runnable: impl Fn(&ProcessorInfo) -> bool, skipped: &str) -> Self {
+        let selected = |p: &ProcessorInfo| {
+            only.is_empty() || only.iter().any(|name| name == p.table_name || name == p.processor_name)
+        };
+        for processor in self.processors.iter().filter(|p| selected(*p) && !runnable(*p)) {
+            warn!("{} {}, skipping", processor.table_name, skipped);
+        }
+        Self {
+            processors: self.processors.iter().filter(|p| selected(*p) && runnable(*p)).cloned().collect(),
+        }
+    }
+
+    /// Processes blocks `from..=to` again, `batch_blocks` at a time. The rows of a batch are
+    /// deleted, waiting for the delete to finish, only after `load` returned its blocks, so a
+    /// failing source leaves the rest of the range as it was.
+    ///
+    /// Processors carrying state read it from their rows before each block, so the range must
+    /// reach their last row: rows after `to` would still hold state derived from the rows
+    /// being replaced, and the range is refused when any exist.
+    async fn reindex<F, Fut>(
+        &self,
+        client: &Arc<Client>,
+        range: RangeInclusive<u64>,
+        dry_run: bool,
+        source: &str,
+        batch_blocks: u64,
+        mut load: F,
+    ) -> Result<()>
+    where
+        F: FnMut(u64, u64) -> Fut,
+        Fut: std::future::Future<Output = Result<Vec<EthereumBlockData>>>,
+    {
+        let (from, to) = range.into_inner();
+        let mut writers = Vec::with_capacity(self.processors.len());
+        for processor in &self.processors {
+            let table = get_table(processor.table_name)
+                .expect(&format!("Table definition not found for {}", processor.table_name));
+            let writer = DbWriter::new(client, table)?;
+            if processor.carries_state && to < u64::MAX {
+                let later = writer.count_range(to + 1, u64::MAX).await?;
+                eyre::ensure!(
+                    later == 0,
+                    "{} carries state across blocks and has {} rows after block {}; reindex it up to its last block",
+                    processor.table_name,
+                    later,
+                    to
+                );
+            }
+            writers.push(writer);
+        }
+
+        info!("Reindexing blocks {}..={} from {}: {:?}", from, to, source, self.list_processors());
+        if dry_run {
+            for (processor, writer) in self.processors.iter().zip(&writers) {
+                let rows = writer.count_range(from, to).await?;
+                info!("Dry run: would delete and rebuild {} rows of {}", rows, processor.table_name);
+            }
+            return Ok(());
+        }
+
+        let started = Instant::now();
+        let total_blocks = (to - from).saturating_add(1);
+        let mut batch_start = from;
+        while batch_start <= to {
+            let batch_end = batch_start.saturating_add(batch_blocks - 1).min(to);
+            let blocks = load(batch_start, batch_end).await?;
+            for writer in &writers {
+                writer.revert_range(batch_start, batch_end).await?;
+            }
+
+            for block_data in &blocks {
+                let components = ProcessingComponents {
+                    eth_api: None,
+                    block_traces: None,
+                    client: Arc::clone(client),
+                };
+                if let Err(e) = self.process_block_data(block_data, components).await {
+                    let block_number = block_data.0.num_hash().number;
+                    eyre::bail!(
+                        "Reindexing stopped at block {}: {}. Rows of blocks {}..={} were deleted, run again with --from {} to rebuild them",
+                        block_number,
+                        e,
+                        block_number,
+                        batch_end,
+                        block_number
+                    );
+                }
+            }
+
+            let done_blocks = (batch_end - from).saturating_add(1);
+            info!(
+                "Reindexed blocks {}..={} ({} read), {}/{} blocks ({:.1}%) in {:.0}s",
+                batch_start,
+                batch_end,
+                blocks.len(),
+                done_blocks,
+                total_blocks,
+                done_blocks as f64 * 100.0 / total_blocks as f64,
+                started.elapsed().as_secs_f64(),
+            );
+            if batch_end == u64::MAX { break; }
+            batch_start = batch_end + 1;
+        }
+
+        Ok(())
+    }
+
+    pub async fn process_blocks<EthApi>(
         &self,
         blocks_and_receipts: Vec<EthereumBlockData>,
         client: &Arc<Client>,
-        provider: Node::Provider,
         eth_api: &EthApi,
         trace_api: &TraceApi<EthApi>,
     ) -> Result<()>
     where
-        Node: FullNodeComponents,
-        EthApi: FullEthApi + EthApiTypes,
+        EthApi: FullEthApi + EthApiTypes + 'static,
         <EthApi as EthApiTypes>::NetworkTypes: RpcTypes + Network,
         <<EthApi as EthApiTypes>::NetworkTypes as RpcTypes>::TransactionRequest: Default + TransactionBuilder<<EthApi as EthApiTypes>::NetworkTypes>,
     {
-        let eth_api_arc = Arc::new(eth_api.clone());
+        let eth_api_arc: Arc<dyn ContractCaller> = Arc::new(eth_api.clone());
+        let tracing_enabled = CONFIG.tracing_enabled && self.processors.iter().any(|p| p.requires_traces);
         for (block, receipts) in blocks_and_receipts {
             let block_number = block.num_hash().number;
-            let block_id = BlockId::Number(BlockNumberOrTag::from(block_number));
 
-            let block_traces = match trace_api.replay_block_transactions(
-                block_id,
-                HashSet::from_iter(vec![TraceType::Trace])
-            ).await {
-                Ok(traces) => traces,
-                Err(e) => {
-                    warn!("Failed to get traces for block {}: {}", block_number, e);
-                    None
+            let mut block_traces = Ok(None);
+            if tracing_enabled {
+                for attempt in 1..=TRACE_ATTEMPTS {
+                    block_traces = trace_pool_manager_transactions(&block, &receipts, trace_api).await;
+                    let Err(e) = &block_traces else { break; };
+                    warn!("Failed to trace block {} (attempt {}/{}): {}", block_number, attempt, TRACE_ATTEMPTS, e);
+                    if attempt < TRACE_ATTEMPTS { tokio::time::sleep(Duration::from_secs(attempt as u64)).await; }
                 }
-            };
-
-            let components = ProcessingComponents {
-                eth_api: Arc::clone(&eth_api_arc),
-                block_traces,
-                provider: provider.clone(),
-                client: Arc::clone(client),
-            };
+            }
 
             let block_data = (block, receipts);
-            if let Err(e) = self.process_block_data(&block_data, components).await {
-                warn!("Failed to process block {}: {}", block_number, e);
+            let result = match block_traces {
+                Ok(block_traces) => {
+                    let components = ProcessingComponents {
+                        eth_api: Some(Arc::clone(&eth_api_arc)),
+                        block_traces,
+                        client: Arc::clone(client),
+                    };
+                    self.process_block_data(&block_data, components).await
+                }
+                Err(trace_error) => {
+                    let components = ProcessingComponents {
+                        eth_api: Some(Arc::clone(&eth_api_arc)),
+                        block_traces: None,
+                        client: Arc::clone(client),
+                    };
+                    let without_traces = self.without_traces();
+                    let skipped: Vec<&str> = self.processors.iter().filter(|p| p.requires_traces).map(|p| p.table_name).collect();
+                    let trace_failure = format!("{}: no call traces ({})", skipped.join(", "), trace_error);
+                    match without_traces.process_block_data(&block_data, components).await {
+                        Ok(()) => Err(eyre::eyre!("{}", trace_failure)),
+                        Err(e) => Err(eyre::eyre!("{}, {}", e, trace_failure)),
+                    }
+                }
+            };
+            if let Err(e) = result {
+                warn!("exex{{id=\"univ4-exex-indexer\"}}: Block {} failures - {}", block_number, e);
             }
         }
 
         Ok(())
     }
 
+    /// Runs every processor on the block concurrently. Fails with the errors of the processors
+    /// that failed once all of them have finished; the rows of the others are written.
     pub async fn process_block_data(
         &self,
         block_data: &EthereumBlockData,
-        components: ProcessingComponents<Node, EthApi>,
-    ) -> Result<()>
-    where
-        Node: FullNodeComponents,
-        EthApi: FullEthApi + EthApiTypes,
-        <EthApi as EthApiTypes>::NetworkTypes: RpcTypes + Network,
-        <<EthApi as EthApiTypes>::NetworkTypes as RpcTypes>::TransactionRequest: Default + TransactionBuilder<<EthApi as EthApiTypes>::NetworkTypes>,
-    {
+        components: ProcessingComponents,
+    ) -> Result<()> {
         let block_number = block_data.0.num_hash().number;
         let shared_block_data = std::sync::Arc::new(block_data.clone());
 
@@ -221,14 +668,14 @@ impl<Node: FullNodeComponents, EthApi: FullEthApi> Indexer<Node, EthApi> {
                 }
             });
 
-            tasks.push(task);
+            tasks.push((processor_name, task));
         }
 
         let mut total_records = 0usize;
-        let mut event_results: Vec<(&str, usize, std::time::Duration)> = Vec::with_capacity(tasks.len());
+        let mut event_results: Vec<(&str, usize, Duration)> = Vec::with_capacity(tasks.len());
         let mut failed_events: Vec<(&str, String)> = Vec::new();
 
-        for task in tasks {
+        for (processor_name, task) in tasks {
             match task.await {
                 Ok(Ok((name, records, duration))) => {
                     total_records += records;
@@ -238,7 +685,7 @@ impl<Node: FullNodeComponents, EthApi: FullEthApi> Indexer<Node, EthApi> {
                     failed_events.push((name, error));
                 }
                 Err(e) => {
-                    warn!("Task join error: {}", e);
+                    failed_events.push((processor_name, e.to_string()));
                 }
             }
         }
@@ -266,14 +713,70 @@ impl<Node: FullNodeComponents, EthApi: FullEthApi> Indexer<Node, EthApi> {
                 .iter()
                 .map(|(name, error)| format!("{}: {}", name, error))
                 .collect();
-
-            warn!(
-                "exex{{id=\"univ4-exex-indexer\"}}: Block {} failures - {}",
-                block_number,
-                failure_summary.join(", ")
-            );
+            eyre::bail!("{}", failure_summary.join(", "));
         }
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+/// Traces only the transactions of the block that called or emitted a log from the
+/// PoolManager. Returns `None` when the block has no such transaction, and fails when any of
+/// them cannot be traced, as processors would otherwise miss its rows. Replaying one
+/// transaction re-executes every transaction before it in the block, so when that adds up
+/// to more than the block itself, the whole block is replayed once and filtered instead.
+async fn trace_pool_manager_transactions<EthApi>(
+    block: &EthereumBlock,
+    receipts: &EthereumReceipts,
+    trace_api: &TraceApi<EthApi>,
+) -> Result<Option<Arc<Vec<TraceResultsWithTransactionHash>>>>
+where
+    EthApi: FullEthApi + EthApiTypes,
+{
+    let transactions: Vec<_> = block
+        .body()
+        .transactions
+        .iter()
+        .zip(receipts.iter())
+        .enumerate()
+        .filter(|(_, (tx, receipt))| {
+            tx.to() == Some(UNIV4_FACTORY_CONTRACT_ADDRESS)
+                || receipt.logs.iter().any(|log| log.address == UNIV4_FACTORY_CONTRACT_ADDRESS)
+        })
+        .map(|(tx_idx, (tx, _))| (tx_idx, *tx.hash()))
+        .collect();
+
+    if transactions.is_empty() { return Ok(None); }
+
+    let replayed_transactions: usize = transactions.iter().map(|(tx_idx, _)| tx_idx + 1).sum();
+    if replayed_transactions > block.body().transactions.len() {
+        let block_number = block.num_hash().number;
+        let block_id = BlockId::Number(BlockNumberOrTag::from(block_number));
+        let tx_hashes: HashSet<_> = transactions.iter().map(|(_, hash)| *hash).collect();
+        let traces = trace_api
+            .replay_block_transactions(block_id, HashSet::from_iter(vec![TraceType::Trace]))
+            .await
+            .map_err(|e| eyre::eyre!("replaying block {}: {}", block_number, e))?
+            .ok_or_else(|| eyre::eyre!("block {} not found for tracing", block_number))?;
+        let traces: Vec<_> = traces.into_iter().filter(|trace| tx_hashes.contains(&trace.transaction_hash)).collect();
+        eyre::ensure!(
+            traces.len() == tx_hashes.len(),
+            "block {} replay traced {} of {} PoolManager transactions",
+            block_number,
+            traces.len(),
+            tx_hashes.len()
+        );
+        return Ok(Some(Arc::new(traces)));
+    }
+
+    let mut traces = Vec::with_capacity(transactions.len());
+    let mut failed = Vec::new();
+    for (_, transaction_hash) in transactions {
+        match trace_api.replay_transaction(transaction_hash, HashSet::from_iter(vec![TraceType::Trace])).await {
+            Ok(full_trace) => traces.push(TraceResultsWithTransactionHash { full_trace, transaction_hash }),
+            Err(e) => failed.push(format!("{}: {}", transaction_hash, e)),
+        }
+    }
+    eyre::ensure!(failed.is_empty(), "tracing transactions failed: {}", failed.join(", "));
+
+    Ok(Some(Arc::new(traces)))
+}
@@ -1,18 +1,22 @@
+mod abi;
+mod cli;
 mod utils;
+mod config;
 mod indexer;
 mod processors;
 mod schema;
 mod storage;
+mod traces;
 #[cfg(feature = "jemalloc")]
 #[global_allocator]
 static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+use cli::IndexerCli;
 use utils::connect_to_clickhouse;
 use storage::init_tables;
 use indexer::{Indexer, EthereumBlockData};
 use eyre::Result;
 use futures::{TryStreamExt};
 use reth_ethereum::{
-    cli::Cli,
     chainspec::EthereumHardforks,
     exex::{ExExContext, ExExEvent, ExExNotification},
     node::{
@@ -36,11 +40,11 @@ async fn indexer_exex<Node, EthApi>(
     mut ctx: ExExContext<Node>,
     rpc_handle: oneshot::Receiver<RpcHandle<Node, EthApi>>,
     client: Arc<Client>,
-    indexer: Indexer<Node, EthApi>,
+    indexer: Indexer,
 ) -> Result<()>
 where
     Node: FullNodeComponents<Types: NodeTypes<ChainSpec: EthereumHardforks>>,
-    EthApi: FullEthApi + EthApiTypes,
+    EthApi: FullEthApi + EthApiTypes + 'static,
     <EthApi as EthApiTypes>::NetworkTypes: RpcTypes + Network,
     <<EthApi as EthApiTypes>::NetworkTypes as RpcTypes>::TransactionRequest: Default + TransactionBuilder<<EthApi as EthApiTypes>::NetworkTypes>,
     Vec<(reth_primitives_traits::RecoveredBlock<alloy_consensus::Block<alloy_consensus::EthereumTxEnvelope<alloy_consensus::TxEip4844>>>, Vec<reth_primitives::Receipt>)>: FromIterator<(reth_primitives_traits::RecoveredBlock<<<<Node as FullNodeTypes>::Types as NodeTypes>::Primitives as NodePrimitives>::Block>, Vec<<<<Node as FullNodeTypes>::Types as NodeTypes>::Primitives as NodePrimitives>::Receipt>)>
@@ -71,7 +75,6 @@ where
                 if let Err(e) = indexer.process_blocks(
                     blocks_and_receipts,
                     &client,
-                    ctx.provider().clone(),
                     eth_api,
                     &trace_api
                 ).await {
@@ -82,6 +85,26 @@ where
             },
             ExExNotification::ChainReorged { old, new } => {
                 info!(from_chain = ?old.range(), to_chain = ?new.range(), "Received reorg");
+
+                let block_numbers: Vec<i64> = old.blocks_iter().map(|b| b.num_hash().number as i64).collect();
+                if let Err(e) = indexer.revert_blocks(&block_numbers, &client).await {
+                    warn!("Failed to revert reorged blocks: {}", e);
+                }
+
+                let blocks_and_receipts: Vec<EthereumBlockData> = new.blocks_and_receipts()
+                    .map(|(block, receipts)| (block.clone(), receipts.clone()))
+                    .collect();
+
+                if let Err(e) = indexer.process_blocks(
+                    blocks_and_receipts,
+                    &client,
+                    eth_api,
+                    &trace_api
+                ).await {
+                    warn!("Failed to process reorged blocks: {}", e);
+                }
+
+                ctx.events.send(ExExEvent::FinishedHeight(new.tip().num_hash()))?;
             },
         }
     }
@@ -90,7 +113,14 @@ where
 }
 
 fn main() -> Result<()> {
-    Cli::parse_args().run(|builder, _| {
+    abi::init()?;
+
+    let cli = match IndexerCli::parse_args() {
+        IndexerCli::Reth(cli) => cli,
+        IndexerCli::Indexer(command) => return command.run(),
+    };
+
+    cli.run(|builder, _| {
         Box::pin(async move {
             let client = Arc::new(connect_to_clickhouse().await?);
             init_tables(&client).await?;
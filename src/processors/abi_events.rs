@@ -0,0 +1,112 @@
+use crate::values;
+use crate::abi::{decoded_inputs, event_for_table, format_value, sources};
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::reader::{abi_contracts, remember_abi_contract};
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use alloy::{dyn_abi::EventExt, primitives::{Address, TxHash}};
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use tracing::debug;
+
+const CHAIN_ID: u32 = 1;
+
+/// Writes one row per log of the ABI event behind the writer's table, emitted by one of its
+/// source's configured addresses or by a contract its factory announced. Event inputs follow
+/// the standard log columns in declaration order.
+pub async fn process_abi_events(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let Some((source, abi_event)) = event_for_table(writer.table_name()) else { return Ok(()); };
+    let selector = abi_event.event.selector();
+
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let mut logs: Vec<(usize, TxHash, usize, &alloy::primitives::Log)> = Vec::new();
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if let Some(contract) = source.discovered_contract(log.address, log.topics(), &log.data.data) {
+                remember_abi_contract(&source.name, contract);
+            }
+            if log.topics().get(0) != Some(&selector) { continue; }
+            logs.push((tx_idx, *tx.hash(), log_idx, log));
+        }
+    }
+    if logs.is_empty() { return Ok(()); }
+
+    let candidates: Vec<Address> = logs
+        .iter()
+        .map(|(_, _, _, log)| log.address)
+        .filter(|address| !source.addresses.contains(address))
+        .collect();
+    let discovered = if source.factory.is_some() && !candidates.is_empty() {
+        abi_contracts(&components.client, &source.name, &candidates).await?
+    } else {
+        Default::default()
+    };
+
+    for (tx_idx, tx_hash, log_idx, log) in logs {
+        if !source.addresses.contains(&log.address) && !discovered.contains(&log.address) { continue; }
+
+        match abi_event.event.decode_log_parts(log.topics().iter().copied(), &log.data.data) {
+            Ok(decoded) => {
+                let event_id = format!("{}#{}", tx_hash, log_idx);
+                let mut record = values![
+                    CHAIN_ID as i64,
+                    block_number as i64,
+                    block_timestamp,
+                    tx_hash,
+                    tx_idx as i64,
+                    log_idx as i64,
+                    log.address,
+                    event_id,
+                ];
+                record.extend(decoded_inputs(&abi_event.event, decoded).iter().map(format_value));
+                writer.write_record(record);
+            }
+            Err(e) => { debug!("Failed to decode {} {} event: {:?}", source.name, abi_event.event.name, e); }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes one row per contract announced by the factory of a configured ABI source, so the
+/// contracts stay indexed across restarts.
+pub async fn process_abi_contracts(
+    block_data: &EthereumBlockData,
+    _components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            for source in sources().iter() {
+                let Some(contract) = source.discovered_contract(log.address, log.topics(), &log.data.data) else { continue; };
+                remember_abi_contract(&source.name, contract);
+
+                writer.write_record(values![
+                    CHAIN_ID as i64,
+                    block_number as i64,
+                    block_timestamp,
+                    tx.hash(),
+                    tx_idx as i64,
+                    log_idx as i64,
+                    source.name.as_str(),
+                    log.address,
+                    contract,
+                ]);
+            }
+        }
+    }
+
+    Ok(())
+}
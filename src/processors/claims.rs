@@ -0,0 +1,216 @@
+use crate::values;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::reader::latest_rows_before;
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use crate::utils::currency_of;
+use alloy::{sol, sol_types::SolEvent, primitives::{address, Address, I256, U256}};
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::debug;
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const CHAIN_ID: u32 = 1;
+
+sol! {
+    event Transfer(address caller, address indexed from, address indexed to, uint256 indexed id, uint256 amount);
+    event Approval(address indexed owner, address indexed spender, uint256 indexed id, uint256 amount);
+    event OperatorSet(address indexed owner, address indexed operator, bool approved);
+}
+
+pub async fn process_uni_v4_claims_transfers(
+    block_data: &EthereumBlockData,
+    _components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+            if log.topics().get(0) != Some(&Transfer::SIGNATURE_HASH) { continue; }
+
+            match Transfer::decode_raw_log(log.topics(), &log.data.data) {
+                Ok(evt) => {
+                    let event_id = format!("{}#{}", tx.hash(), log_idx);
+                    writer.write_record(values![
+                        CHAIN_ID as i64,
+                        block_number as i64,
+                        block_timestamp,
+                        tx.hash(),
+                        tx_idx as i64,
+                        log_idx as i64,
+                        log.address,
+                        event_id,
+                        evt.caller,
+                        evt.from,
+                        evt.to,
+                        currency_of(evt.id),
+                        evt.amount,
+                    ]);
+                }
+                Err(e) => { debug!("Failed to decode univ4 claims transfer event: {:?}", e); }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn process_uni_v4_claims_approvals(
+    block_data: &EthereumBlockData,
+    _components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+
+            let decoded = if log.topics().get(0) == Some(&Approval::SIGNATURE_HASH) {
+                Approval::decode_raw_log(log.topics(), &log.data.data)
+                    .map(|evt| ("approval", evt.owner, evt.spender, currency_of(evt.id), evt.amount, evt.amount > U256::ZERO))
+            } else if log.topics().get(0) == Some(&OperatorSet::SIGNATURE_HASH) {
+                OperatorSet::decode_raw_log(log.topics(), &log.data.data)
+                    .map(|evt| ("operator_set", evt.owner, evt.operator, Address::ZERO, U256::ZERO, evt.approved))
+            } else {
+                continue;
+            };
+
+            match decoded {
+                Ok((event_type, owner, spender, currency, amount, approved)) => {
+                    let event_id = format!("{}#{}", tx.hash(), log_idx);
+                    writer.write_record(values![
+                        CHAIN_ID as i64,
+                        block_number as i64,
+                        block_timestamp,
+                        tx.hash(),
+                        tx_idx as i64,
+                        log_idx as i64,
+                        log.address,
+                        event_id,
+                        event_type,
+                        owner,
+                        spender,
+                        currency,
+                        amount,
+                        approved,
+                    ]);
+                }
+                Err(e) => { debug!("Failed to decode univ4 claims approval event: {:?}", e); }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct BalanceState {
+    account: String,
+    currency: String,
+    balance: String,
+}
+
+/// Writes one row per account touched by a claims `Transfer` (mint, burn or transfer),
+/// carrying the account's claim balance of the currency after the event. The latest row
+/// per `(account, currency)` is the account's current balance inside the PoolManager.
+pub async fn process_uni_v4_claims_balances(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let mut events = Vec::new();
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+            if log.topics().get(0) != Some(&Transfer::SIGNATURE_HASH) { continue; }
+
+            match Transfer::decode_raw_log(log.topics(), &log.data.data) {
+                Ok(evt) => events.push((tx_idx, tx.hash(), log_idx, log.address, evt)),
+                Err(e) => { debug!("Failed to decode univ4 claims transfer event: {:?}", e); }
+            }
+        }
+    }
+
+    if events.is_empty() { return Ok(()); }
+
+    let keys: Vec<(Address, Address)> = events
+        .iter()
+        .flat_map(|e| [(e.4.from, currency_of(e.4.id)), (e.4.to, currency_of(e.4.id))])
+        .filter(|(account, _)| *account != Address::ZERO)
+        .collect();
+    let mut balances = load_balances(&components.client, block_number, &keys).await?;
+
+    for (tx_idx, tx_hash, log_idx, log_address, evt) in events {
+        let currency = currency_of(evt.id);
+        let amount = I256::try_from(evt.amount).unwrap_or(I256::MAX);
+        let event_id = format!("{}#{}", tx_hash, log_idx);
+
+        for (account, delta) in [(evt.from, -amount), (evt.to, amount)] {
+            if account == Address::ZERO { continue; }
+
+            let balance = balances.entry((account, currency)).or_default();
+            *balance = if delta.is_negative() {
+                balance.saturating_sub(delta.unsigned_abs())
+            } else {
+                balance.saturating_add(delta.unsigned_abs())
+            };
+
+            writer.write_record(values![
+                CHAIN_ID as i64,
+                block_number as i64,
+                block_timestamp,
+                tx_hash,
+                tx_idx as i64,
+                log_idx as i64,
+                log_address,
+                event_id.as_str(),
+                account,
+                currency,
+                delta,
+                *balance,
+            ]);
+        }
+    }
+
+    Ok(())
+}
+
+async fn load_balances(
+    client: &clickhouse::Client,
+    block_number: u64,
+    keys: &[(Address, Address)],
+) -> Result<HashMap<(Address, Address), U256>> {
+    let rows: Vec<BalanceState> = latest_rows_before(
+        client,
+        "uni_v4_claims_balances",
+        "account, currency, toString(balance) AS balance",
+        &[
+            ("account", keys.iter().map(|(account, _)| account.to_checksum(Some(1))).collect()),
+            ("currency", keys.iter().map(|(_, currency)| currency.to_checksum(Some(1))).collect()),
+        ],
+        block_number,
+    )
+    .await?;
+
+    let mut balances = HashMap::with_capacity(rows.len());
+    for row in rows {
+        let (Ok(account), Ok(currency)) = (row.account.parse::<Address>(), row.currency.parse::<Address>()) else { continue; };
+        balances.insert((account, currency), row.balance.parse::<U256>().unwrap_or_default());
+    }
+    Ok(balances)
+}
@@ -0,0 +1,191 @@
+use crate::values;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::reader::latest_rows_before;
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use crate::utils::DYNAMIC_FEE_FLAG;
+use alloy::{sol, sol_types::SolEvent, primitives::{address, Address, FixedBytes}};
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::debug;
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const CHAIN_ID: u32 = 1;
+
+sol! {
+    event Initialize(
+        bytes32 indexed id,
+        address indexed currency0,
+        address indexed currency1,
+        uint24 fee,
+        int24 tickSpacing,
+        address hooks,
+        uint160 sqrtPriceX96,
+        int24 tick
+    );
+    event Swap(
+        bytes32 indexed id,
+        address indexed sender,
+        int128 amount0,
+        int128 amount1,
+        uint160 sqrtPriceX96,
+        uint128 liquidity,
+        int24 tick,
+        uint24 fee
+    );
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct FeeState {
+    pool_id: String,
+    fee: u32,
+    is_dynamic_fee: bool,
+}
+
+/// Writes a row whenever the effective swap fee of a pool changes: once at `Initialize`
+/// (the static fee, or 0 for dynamic-fee pools whose fee is only known once they swap),
+/// then for every `Swap` whose `fee` differs from the pool's previous one. For
+/// dynamic-fee pools this is the fee set by the hook, either stored or overridden per swap.
+pub async fn process_uni_v4_fee_changes(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let mut events = Vec::new();
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+
+            let decoded = if log.topics().get(0) == Some(&Initialize::SIGNATURE_HASH) {
+                Initialize::decode_raw_log(log.topics(), &log.data.data).map(|evt| {
+                    let (is_dynamic_fee, fee) = initial_fee(evt.fee.to::<u32>());
+                    (evt.id, "initialize", Some(is_dynamic_fee), fee)
+                })
+            } else if log.topics().get(0) == Some(&Swap::SIGNATURE_HASH) {
+                Swap::decode_raw_log(log.topics(), &log.data.data).map(|evt| (evt.id, "swap", None, evt.fee.to::<u32>()))
+            } else {
+                continue;
+            };
+
+            match decoded {
+                Ok((pool_id, source, is_dynamic_fee, fee)) => {
+                    events.push((tx_idx, tx.hash(), log_idx, log.address, pool_id, source, is_dynamic_fee, fee));
+                }
+                Err(e) => { debug!("Failed to decode univ4 fee change event: {:?}", e); }
+            }
+        }
+    }
+
+    if events.is_empty() { return Ok(()); }
+
+    let pool_ids: Vec<FixedBytes<32>> = events.iter().map(|e| e.4).collect();
+    let mut state = load_fee_state(&components.client, block_number, &pool_ids).await?;
+
+    for (tx_idx, tx_hash, log_idx, log_address, pool_id, source, is_dynamic_fee, fee) in events {
+        let Some((is_dynamic_fee, previous_fee)) = apply_fee(&mut state, pool_id, source, is_dynamic_fee, fee) else { continue; };
+
+        let event_id = format!("{}#{}", tx_hash, log_idx);
+        writer.write_record(values![
+            CHAIN_ID as i64,
+            block_number as i64,
+            block_timestamp,
+            tx_hash,
+            tx_idx as i64,
+            log_idx as i64,
+            log_address,
+            event_id,
+            pool_id,
+            source,
+            is_dynamic_fee,
+            previous_fee as i64,
+            fee as i64,
+        ]);
+    }
+
+    Ok(())
+}
+
+/// Whether the fee set at `Initialize` is the dynamic fee flag, and the pool's fee until it
+/// swaps.
+fn initial_fee(fee: u32) -> (bool, u32) {
+    if fee == DYNAMIC_FEE_FLAG { (true, 0) } else { (false, fee) }
+}
+
+/// Records `fee` as the pool's fee, returning whether the pool has a dynamic fee and its
+/// previous fee, or `None` for swaps that leave a known fee unchanged.
+fn apply_fee(
+    state: &mut HashMap<FixedBytes<32>, (u32, bool)>,
+    pool_id: FixedBytes<32>,
+    source: &str,
+    is_dynamic_fee: Option<bool>,
+    fee: u32,
+) -> Option<(bool, u32)> {
+    let previous = state.get(&pool_id).copied();
+    let is_dynamic_fee = is_dynamic_fee.or(previous.map(|(_, dynamic)| dynamic)).unwrap_or(false);
+    let previous_fee = previous.map(|(fee, _)| fee).unwrap_or(0);
+
+    if source == "swap" && previous.is_some() && previous_fee == fee { return None; }
+    state.insert(pool_id, (fee, is_dynamic_fee));
+    Some((is_dynamic_fee, previous_fee))
+}
+
+async fn load_fee_state(
+    client: &clickhouse::Client,
+    block_number: u64,
+    pool_ids: &[FixedBytes<32>],
+) -> Result<HashMap<FixedBytes<32>, (u32, bool)>> {
+    let rows: Vec<FeeState> = latest_rows_before(
+        client,
+        "uni_v4_fee_changes",
+        "toString(pool_id) AS pool_id, fee, is_dynamic_fee",
+        &[("pool_id", pool_ids.iter().map(|id| id.to_string()).collect())],
+        block_number,
+    )
+    .await?;
+
+    let mut state = HashMap::with_capacity(rows.len());
+    for row in rows {
+        let Ok(pool_id) = row.pool_id.parse::<FixedBytes<32>>() else { continue; };
+        state.insert(pool_id, (row.fee, row.is_dynamic_fee));
+    }
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dynamic_fee_flag_is_detected_at_initialize() {
+        assert_eq!(initial_fee(DYNAMIC_FEE_FLAG), (true, 0));
+        assert_eq!(initial_fee(3000), (false, 3000));
+        assert_eq!(initial_fee(0), (false, 0));
+    }
+
+    #[test]
+    fn only_fee_changes_are_written() {
+        let dynamic = FixedBytes::repeat_byte(1);
+        let fixed = FixedBytes::repeat_byte(2);
+        let mut state = HashMap::new();
+
+        assert_eq!(apply_fee(&mut state, dynamic, "initialize", Some(true), 0), Some((true, 0)));
+        assert_eq!(apply_fee(&mut state, dynamic, "swap", None, 2500), Some((true, 0)));
+        assert_eq!(apply_fee(&mut state, dynamic, "swap", None, 2500), None);
+        assert_eq!(apply_fee(&mut state, dynamic, "swap", None, 100), Some((true, 2500)));
+        assert_eq!(state[&dynamic], (100, true));
+
+        assert_eq!(apply_fee(&mut state, fixed, "initialize", Some(false), 500), Some((false, 0)));
+        assert_eq!(apply_fee(&mut state, fixed, "swap", None, 500), None);
+
+        // Swaps of pools without a known fee are written once
+        let unknown = FixedBytes::repeat_byte(3);
+        assert_eq!(apply_fee(&mut state, unknown, "swap", None, 0), Some((false, 0)));
+        assert_eq!(apply_fee(&mut state, unknown, "swap", None, 0), None);
+    }
+}
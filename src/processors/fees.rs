@@ -0,0 +1,485 @@
+use crate::values;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::reader::latest_rows_before;
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use crate::utils::position_key;
+use alloy::{sol, sol_types::SolEvent, primitives::{address, Address, FixedBytes, TxHash, U256}};
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::debug;
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const CHAIN_ID: u32 = 1;
+const PIPS_DENOMINATOR: u64 = 1_000_000;
+
+sol! {
+    event Swap(
+        bytes32 indexed id,
+        address indexed sender,
+        int128 amount0,
+        int128 amount1,
+        uint160 sqrtPriceX96,
+        uint128 liquidity,
+        int24 tick,
+        uint24 fee
+    );
+    event ModifyLiquidity(
+        bytes32 indexed id,
+        address indexed sender,
+        int24 tickLower,
+        int24 tickUpper,
+        int256 liquidityDelta,
+        bytes32 salt
+    );
+    event ProtocolFeeUpdated(bytes32 indexed id, uint24 protocolFee);
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct ProtocolFeeState {
+    pool_id: String,
+    protocol_fee: u32,
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct FeeGrowthState {
+    pool_id: String,
+    fee_growth_global0_x128: String,
+    fee_growth_global1_x128: String,
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct OpenPosition {
+    pool_id: String,
+    position_id: String,
+    owner: String,
+    tick_lower: i32,
+    tick_upper: i32,
+    liquidity: String,
+}
+
+#[derive(Debug, Clone)]
+struct Position {
+    owner: Address,
+    tick_lower: i32,
+    tick_upper: i32,
+    liquidity: u128,
+}
+
+enum FeeEvent {
+    ProtocolFee(FixedBytes<32>, u32),
+    Liquidity(ModifyLiquidity),
+    Swap(Swap),
+}
+
+/// Fee charged on a swap, in the input currency.
+struct SwapFee {
+    zero_for_one: bool,
+    protocol_fee: u32,
+    fee_amount: u128,
+    protocol_fee_amount: u128,
+    lp_fee_amount: u128,
+}
+
+impl SwapFee {
+    /// The input currency is the one with a negative delta. Both fees are charged on the
+    /// gross input: the protocol fee at its directional rate and the swap fee (`fee` of the
+    /// event, which already includes the protocol fee); LPs earn the difference.
+    fn new(evt: &Swap, protocol_fees: &HashMap<FixedBytes<32>, u32>) -> Self {
+        let zero_for_one = evt.amount0 < 0;
+        let amount_in = if zero_for_one { evt.amount0.unsigned_abs() } else { evt.amount1.unsigned_abs() };
+        let packed = protocol_fees.get(&evt.id).copied().unwrap_or(0);
+        let protocol_fee = if zero_for_one { packed & 0xfff } else { packed >> 12 };
+
+        let fee_amount = mul_div(amount_in, evt.fee.to::<u64>(), PIPS_DENOMINATOR);
+        let protocol_fee_amount = mul_div(amount_in, protocol_fee as u64, PIPS_DENOMINATOR).min(fee_amount);
+        Self {
+            zero_for_one,
+            protocol_fee,
+            fee_amount,
+            protocol_fee_amount,
+            lp_fee_amount: fee_amount - protocol_fee_amount,
+        }
+    }
+
+    fn split(&self, amount: u128) -> (u128, u128) {
+        if self.zero_for_one { (amount, 0) } else { (0, amount) }
+    }
+}
+
+/// Writes one row per `Swap` with the fee it paid, split between the protocol and LPs, and
+/// the pool's cumulative fee growth per unit of liquidity (Q128, as in the PoolManager).
+/// Fee growth uses the liquidity reported by the swap, so swaps crossing ticks are an
+/// approximation.
+pub async fn process_uni_v4_fees(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let events = collect_fee_events(block_data);
+    let pool_ids: Vec<FixedBytes<32>> = events
+        .iter()
+        .filter_map(|(.., event)| match event { FeeEvent::Swap(evt) => Some(evt.id), _ => None })
+        .collect();
+    if pool_ids.is_empty() { return Ok(()); }
+
+    let mut protocol_fees = load_protocol_fees(&components.client, block_number, &pool_ids).await?;
+    let mut fee_growth = load_fee_growth(&components.client, block_number, &pool_ids).await?;
+
+    for (tx_idx, tx_hash, log_idx, event) in events {
+        let evt = match event {
+            FeeEvent::ProtocolFee(pool_id, protocol_fee) => { protocol_fees.insert(pool_id, protocol_fee); continue; }
+            FeeEvent::Liquidity(_) => continue,
+            FeeEvent::Swap(evt) => evt,
+        };
+
+        let fee = SwapFee::new(&evt, &protocol_fees);
+        let (fee_growth0, fee_growth1) = fee_growth.entry(evt.id).or_insert((U256::ZERO, U256::ZERO));
+        if evt.liquidity > 0 {
+            let growth = fee_growth(fee.lp_fee_amount, evt.liquidity);
+            if fee.zero_for_one {
+                *fee_growth0 = fee_growth0.wrapping_add(growth);
+            } else {
+                *fee_growth1 = fee_growth1.wrapping_add(growth);
+            }
+        }
+
+        let (fee_amount0, fee_amount1) = fee.split(fee.fee_amount);
+        let (protocol_fee_amount0, protocol_fee_amount1) = fee.split(fee.protocol_fee_amount);
+        let (lp_fee_amount0, lp_fee_amount1) = fee.split(fee.lp_fee_amount);
+
+        let event_id = format!("{}#{}", tx_hash, log_idx);
+        writer.write_record(values![
+            CHAIN_ID as i64,
+            block_number as i64,
+            block_timestamp,
+            tx_hash,
+            tx_idx as i64,
+            log_idx as i64,
+            UNIV4_FACTORY_CONTRACT_ADDRESS,
+            event_id,
+            evt.id,
+            fee.zero_for_one,
+            evt.fee,
+            fee.protocol_fee as i64,
+            fee_amount0,
+            fee_amount1,
+            protocol_fee_amount0,
+            protocol_fee_amount1,
+            lp_fee_amount0,
+            lp_fee_amount1,
+            evt.liquidity,
+            *fee_growth0,
+            *fee_growth1,
+        ]);
+    }
+
+    Ok(())
+}
+
+/// Writes one row per `Swap` and position in range at the swap's tick, with the position's
+/// pro-rata share of the LP fee. In-range positions are rebuilt from `uni_v4_positions` as
+/// of the previous block, then updated with this block's `ModifyLiquidity` events in order.
+pub async fn process_uni_v4_position_fees(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let events = collect_fee_events(block_data);
+    let pool_ids: Vec<FixedBytes<32>> = events
+        .iter()
+        .filter_map(|(.., event)| match event { FeeEvent::Swap(evt) => Some(evt.id), _ => None })
+        .collect();
+    if pool_ids.is_empty() { return Ok(()); }
+
+    let mut protocol_fees = load_protocol_fees(&components.client, block_number, &pool_ids).await?;
+    let mut positions = load_open_positions(&components.client, block_number, &pool_ids).await?;
+
+    for (tx_idx, tx_hash, log_idx, event) in events {
+        let evt = match event {
+            FeeEvent::ProtocolFee(pool_id, protocol_fee) => { protocol_fees.insert(pool_id, protocol_fee); continue; }
+            FeeEvent::Liquidity(evt) => {
+                let Some(pool_positions) = positions.get_mut(&evt.id) else { continue; };
+                let Ok(liquidity_delta) = i128::try_from(evt.liquidityDelta) else { continue; };
+
+                let position_id = position_key(evt.sender, evt.tickLower.as_i32(), evt.tickUpper.as_i32(), evt.salt);
+                let position = pool_positions.entry(position_id).or_insert(Position {
+                    owner: evt.sender,
+                    tick_lower: evt.tickLower.as_i32(),
+                    tick_upper: evt.tickUpper.as_i32(),
+                    liquidity: 0,
+                });
+                position.liquidity = position.liquidity.saturating_add_signed(liquidity_delta);
+                continue;
+            }
+            FeeEvent::Swap(evt) => evt,
+        };
+
+        let fee = SwapFee::new(&evt, &protocol_fees);
+        if fee.lp_fee_amount == 0 { continue; }
+        let Some(pool_positions) = positions.get(&evt.id) else { continue; };
+
+        let event_id = format!("{}#{}", tx_hash, log_idx);
+        for (position_id, position, share) in in_range_shares(pool_positions, evt.tick.as_i32(), fee.lp_fee_amount) {
+            let (fee_amount0, fee_amount1) = fee.split(share);
+
+            writer.write_record(values![
+                CHAIN_ID as i64,
+                block_number as i64,
+                block_timestamp,
+                tx_hash,
+                tx_idx as i64,
+                log_idx as i64,
+                event_id.as_str(),
+                evt.id,
+                position_id,
+                position.owner,
+                position.tick_lower,
+                position.tick_upper,
+                position.liquidity,
+                fee_amount0,
+                fee_amount1,
+            ]);
+        }
+    }
+
+    Ok(())
+}
+
+fn collect_fee_events(block_data: &EthereumBlockData) -> Vec<(usize, TxHash, usize, FeeEvent)> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+
+    let mut events = Vec::new();
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+
+            let decoded = match log.topics().get(0) {
+                Some(topic) if *topic == Swap::SIGNATURE_HASH => {
+                    Swap::decode_raw_log(log.topics(), &log.data.data).map(FeeEvent::Swap)
+                }
+                Some(topic) if *topic == ModifyLiquidity::SIGNATURE_HASH => {
+                    ModifyLiquidity::decode_raw_log(log.topics(), &log.data.data).map(FeeEvent::Liquidity)
+                }
+                Some(topic) if *topic == ProtocolFeeUpdated::SIGNATURE_HASH => {
+                    ProtocolFeeUpdated::decode_raw_log(log.topics(), &log.data.data)
+                        .map(|evt| FeeEvent::ProtocolFee(evt.id, evt.protocolFee.to::<u32>()))
+                }
+                _ => continue,
+            };
+
+            match decoded {
+                Ok(event) => events.push((tx_idx, *tx.hash(), log_idx, event)),
+                Err(e) => { debug!("Failed to decode univ4 fee event: {:?}", e); }
+            }
+        }
+    }
+    events
+}
+
+/// Fee earned per unit of in-range liquidity, in Q128 like `feeGrowthGlobal{0,1}X128`.
+fn fee_growth(lp_fee_amount: u128, liquidity: u128) -> U256 {
+    (U256::from(lp_fee_amount) << 128) / U256::from(liquidity)
+}
+
+/// Positions in range at `tick` (`tick_lower <= tick < tick_upper`) with their pro-rata share
+/// of `lp_fee_amount` by liquidity.
+fn in_range_shares(
+    positions: &HashMap<FixedBytes<32>, Position>,
+    tick: i32,
+    lp_fee_amount: u128,
+) -> Vec<(&FixedBytes<32>, &Position, u128)> {
+    let in_range: Vec<(&FixedBytes<32>, &Position)> = positions
+        .iter()
+        .filter(|(_, p)| p.liquidity > 0 && p.tick_lower <= tick && tick < p.tick_upper)
+        .collect();
+    let total_liquidity: U256 = in_range.iter().map(|(_, p)| U256::from(p.liquidity)).sum();
+    if total_liquidity.is_zero() { return Vec::new(); }
+
+    in_range
+        .into_iter()
+        .map(|(position_id, position)| {
+            let share = U256::from(lp_fee_amount) * U256::from(position.liquidity) / total_liquidity;
+            (position_id, position, share.to::<u128>())
+        })
+        .collect()
+}
+
+fn mul_div(amount: u128, numerator: u64, denominator: u64) -> u128 {
+    (U256::from(amount) * U256::from(numerator) / U256::from(denominator)).to::<u128>()
+}
+
+fn unique_ids(ids: &[FixedBytes<32>]) -> Vec<String> {
+    let mut ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+async fn load_protocol_fees(
+    client: &clickhouse::Client,
+    block_number: u64,
+    pool_ids: &[FixedBytes<32>],
+) -> Result<HashMap<FixedBytes<32>, u32>> {
+    let rows: Vec<ProtocolFeeState> = latest_rows_before(
+        client,
+        "uni_v4_protocol_fees",
+        "toString(pool_id) AS pool_id, protocol_fee",
+        &[("pool_id", pool_ids.iter().map(|id| id.to_string()).collect())],
+        block_number,
+    )
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| Some((row.pool_id.parse().ok()?, row.protocol_fee)))
+        .collect())
+}
+
+async fn load_fee_growth(
+    client: &clickhouse::Client,
+    block_number: u64,
+    pool_ids: &[FixedBytes<32>],
+) -> Result<HashMap<FixedBytes<32>, (U256, U256)>> {
+    let rows: Vec<FeeGrowthState> = latest_rows_before(
+        client,
+        "uni_v4_fees",
+        "toString(pool_id) AS pool_id, toString(fee_growth_global0_x128) AS fee_growth_global0_x128, \
+         toString(fee_growth_global1_x128) AS fee_growth_global1_x128",
+        &[("pool_id", pool_ids.iter().map(|id| id.to_string()).collect())],
+        block_number,
+    )
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let growth0 = row.fee_growth_global0_x128.parse().unwrap_or_default();
+            let growth1 = row.fee_growth_global1_x128.parse().unwrap_or_default();
+            Some((row.pool_id.parse().ok()?, (growth0, growth1)))
+        })
+        .collect())
+}
+
+async fn load_open_positions(
+    client: &clickhouse::Client,
+    block_number: u64,
+    pool_ids: &[FixedBytes<32>],
+) -> Result<HashMap<FixedBytes<32>, HashMap<FixedBytes<32>, Position>>> {
+    let ids = unique_ids(pool_ids);
+    let rows = client
+        .query(
+            "SELECT pool_id, position_id, owner, tick_lower, tick_upper, liquidity FROM ( \
+                 SELECT toString(pool_id) AS pool_id, toString(position_id) AS position_id, owner, \
+                 tick_lower, tick_upper, toString(liquidity) AS liquidity, is_open \
+                 FROM uni_v4_positions \
+                 WHERE has(?, toString(pool_id)) AND block_number < ? \
+                 ORDER BY block_number DESC, transaction_index DESC, log_index DESC \
+                 LIMIT 1 BY position_id \
+             ) WHERE is_open",
+        )
+        .bind(&ids)
+        .bind(block_number)
+        .fetch_all::<OpenPosition>()
+        .await?;
+
+    let mut positions: HashMap<FixedBytes<32>, HashMap<FixedBytes<32>, Position>> = ids
+        .iter()
+        .filter_map(|id| id.parse().ok())
+        .map(|id| (id, HashMap::new()))
+        .collect();
+    for row in rows {
+        let (Ok(pool_id), Ok(position_id)) = (row.pool_id.parse(), row.position_id.parse()) else { continue; };
+        let position = Position {
+            owner: row.owner.parse().unwrap_or_default(),
+            tick_lower: row.tick_lower,
+            tick_upper: row.tick_upper,
+            liquidity: row.liquidity.parse().unwrap_or_default(),
+        };
+        positions.entry(pool_id).or_default().insert(position_id, position);
+    }
+    Ok(positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::aliases::{I24, U160, U24};
+
+    fn swap(amount0: i128, amount1: i128, fee: u32) -> Swap {
+        Swap {
+            id: FixedBytes::repeat_byte(1),
+            sender: Address::ZERO,
+            amount0,
+            amount1,
+            sqrtPriceX96: U160::ZERO,
+            liquidity: 1_000_000,
+            tick: I24::ZERO,
+            fee: U24::from(fee),
+        }
+    }
+
+    #[test]
+    fn swap_fee_splits_protocol_and_lp_fees_by_direction() {
+        // 0.10% protocol fee for zeroForOne swaps, 0.05% for oneForZero.
+        let protocol_fees = HashMap::from([(FixedBytes::repeat_byte(1), (500 << 12) | 1000)]);
+
+        let fee = SwapFee::new(&swap(-1_000_000, 990_000, 3000), &protocol_fees);
+        assert!(fee.zero_for_one);
+        assert_eq!((fee.protocol_fee, fee.fee_amount, fee.protocol_fee_amount, fee.lp_fee_amount), (1000, 3000, 1000, 2000));
+        assert_eq!(fee.split(fee.lp_fee_amount), (2000, 0));
+
+        let fee = SwapFee::new(&swap(1_980_000, -2_000_000, 3000), &protocol_fees);
+        assert!(!fee.zero_for_one);
+        assert_eq!((fee.protocol_fee, fee.fee_amount, fee.protocol_fee_amount, fee.lp_fee_amount), (500, 6000, 1000, 5000));
+        assert_eq!(fee.split(fee.lp_fee_amount), (0, 5000));
+    }
+
+    #[test]
+    fn swap_fee_caps_protocol_fee_at_swap_fee() {
+        let protocol_fees = HashMap::from([(FixedBytes::repeat_byte(1), 1000)]);
+        let fee = SwapFee::new(&swap(-1_000_000, 990_000, 500), &protocol_fees);
+        assert_eq!((fee.fee_amount, fee.protocol_fee_amount, fee.lp_fee_amount), (500, 500, 0));
+
+        let fee = SwapFee::new(&swap(-1_000_000, 990_000, 3000), &HashMap::new());
+        assert_eq!((fee.protocol_fee_amount, fee.lp_fee_amount), (0, 3000));
+    }
+
+    #[test]
+    fn fee_growth_is_q128_per_unit_of_liquidity() {
+        assert_eq!(fee_growth(3, 3), U256::from(1) << 128);
+        assert_eq!(fee_growth(1, 2), U256::from(1) << 127);
+    }
+
+    #[test]
+    fn lp_fee_is_shared_by_liquidity_among_in_range_positions() {
+        let position = |tick_lower, tick_upper, liquidity| Position { owner: Address::ZERO, tick_lower, tick_upper, liquidity };
+        let positions = HashMap::from([
+            (FixedBytes::repeat_byte(0xa), position(-60, 60, 1)),
+            (FixedBytes::repeat_byte(0xb), position(-120, 0, 3)),
+            (FixedBytes::repeat_byte(0xc), position(60, 120, 5)),
+            (FixedBytes::repeat_byte(0xd), position(-60, 60, 0)),
+        ]);
+        let shares = |tick| {
+            let mut shares: Vec<(u8, u128)> =
+                in_range_shares(&positions, tick, 1000).into_iter().map(|(id, _, share)| (id[0], share)).collect();
+            shares.sort_unstable();
+            shares
+        };
+
+        assert_eq!(shares(-10), vec![(0xa, 250), (0xb, 750)]);
+        // Upper ticks are exclusive.
+        assert_eq!(shares(0), vec![(0xa, 1000)]);
+        assert!(shares(200).is_empty());
+    }
+}
@@ -0,0 +1,193 @@
+use crate::values;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use crate::traces::calls;
+use alloy::{sol, sol_types::{SolCall, SolInterface, SolValue}, primitives::{address, keccak256, Address, FixedBytes, I256, U256}};
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use std::collections::HashMap;
+use tracing::debug;
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const CHAIN_ID: u32 = 1;
+/// Set by `beforeSwap` on the returned LP fee for the PoolManager to use it for the swap.
+const OVERRIDE_FEE_FLAG: u32 = 0x400000;
+/// The fee bits of an LP fee override, without the flags.
+const LP_FEE_MASK: u32 = 0x3FFFFF;
+
+sol! {
+    struct PoolKey {
+        address currency0;
+        address currency1;
+        uint24 fee;
+        int24 tickSpacing;
+        address hooks;
+    }
+
+    struct ModifyLiquidityParams {
+        int24 tickLower;
+        int24 tickUpper;
+        int256 liquidityDelta;
+        bytes32 salt;
+    }
+
+    struct SwapParams {
+        bool zeroForOne;
+        int256 amountSpecified;
+        uint160 sqrtPriceLimitX96;
+    }
+
+    interface IHooks {
+        function beforeInitialize(address sender, PoolKey key, uint160 sqrtPriceX96) external returns (bytes4);
+        function afterInitialize(address sender, PoolKey key, uint160 sqrtPriceX96, int24 tick) external returns (bytes4);
+        function beforeAddLiquidity(address sender, PoolKey key, ModifyLiquidityParams params, bytes hookData) external returns (bytes4);
+        function afterAddLiquidity(address sender, PoolKey key, ModifyLiquidityParams params, int256 delta, int256 feesAccrued, bytes hookData) external returns (bytes4, int256);
+        function beforeRemoveLiquidity(address sender, PoolKey key, ModifyLiquidityParams params, bytes hookData) external returns (bytes4);
+        function afterRemoveLiquidity(address sender, PoolKey key, ModifyLiquidityParams params, int256 delta, int256 feesAccrued, bytes hookData) external returns (bytes4, int256);
+        function beforeSwap(address sender, PoolKey key, SwapParams params, bytes hookData) external returns (bytes4, int256, uint24);
+        function afterSwap(address sender, PoolKey key, SwapParams params, int256 delta, bytes hookData) external returns (bytes4, int128);
+        function beforeDonate(address sender, PoolKey key, uint256 amount0, uint256 amount1, bytes hookData) external returns (bytes4);
+        function afterDonate(address sender, PoolKey key, uint256 amount0, uint256 amount1, bytes hookData) external returns (bytes4);
+    }
+}
+
+/// Writes one row per PoolManager -> hook call found in the block traces: the hook
+/// function, the pool it was called for, gas used, whether it succeeded and the delta
+/// and LP fee override it returned to the PoolManager, if any. The override is stored
+/// without its flag, which is kept in `fee_overridden` as the PoolManager only applies
+/// flagged overrides.
+pub async fn process_uni_v4_hook_calls(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let Some(block_traces) = components.block_traces.as_ref() else { return Ok(()); };
+
+    let block = &block_data.0;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let tx_indexes: HashMap<FixedBytes<32>, usize> = block
+        .body()
+        .transactions
+        .iter()
+        .enumerate()
+        .map(|(tx_idx, tx)| (*tx.hash(), tx_idx))
+        .collect();
+
+    for trace in block_traces.iter() {
+        let Some(&tx_idx) = tx_indexes.get(&trace.transaction_hash) else { continue; };
+
+        for (call_idx, call) in calls(trace).enumerate() {
+            if call.from() != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+
+            let Ok(hook_call) = IHooks::IHooksCalls::abi_decode(call.input()) else { continue; };
+            let (function_name, sender, key) = match &hook_call {
+                IHooks::IHooksCalls::beforeInitialize(c) => ("beforeInitialize", c.sender, &c.key),
+                IHooks::IHooksCalls::afterInitialize(c) => ("afterInitialize", c.sender, &c.key),
+                IHooks::IHooksCalls::beforeAddLiquidity(c) => ("beforeAddLiquidity", c.sender, &c.key),
+                IHooks::IHooksCalls::afterAddLiquidity(c) => ("afterAddLiquidity", c.sender, &c.key),
+                IHooks::IHooksCalls::beforeRemoveLiquidity(c) => ("beforeRemoveLiquidity", c.sender, &c.key),
+                IHooks::IHooksCalls::afterRemoveLiquidity(c) => ("afterRemoveLiquidity", c.sender, &c.key),
+                IHooks::IHooksCalls::beforeSwap(c) => ("beforeSwap", c.sender, &c.key),
+                IHooks::IHooksCalls::afterSwap(c) => ("afterSwap", c.sender, &c.key),
+                IHooks::IHooksCalls::beforeDonate(c) => ("beforeDonate", c.sender, &c.key),
+                IHooks::IHooksCalls::afterDonate(c) => ("afterDonate", c.sender, &c.key),
+            };
+            if key.hooks != call.to() { continue; }
+
+            let pool_id = keccak256(key.abi_encode());
+            let (return_delta0, return_delta1, lp_fee_override) = match call.output() {
+                Some(output) if call.success() => decode_returns(&hook_call, output),
+                _ => (0, 0, 0),
+            };
+
+            let (lp_fee, fee_overridden) = split_fee_override(lp_fee_override);
+            let call_id = format!("{}#{}", trace.transaction_hash, call_idx);
+            writer.write_record(values![
+                CHAIN_ID as i64,
+                block_number as i64,
+                block_timestamp,
+                trace.transaction_hash,
+                tx_idx as i64,
+                call.trace_address(),
+                call_id,
+                call.to(),
+                pool_id,
+                function_name,
+                alloy::primitives::hex::encode_prefixed(call.selector().unwrap_or_default()),
+                sender,
+                call.gas_used() as i64,
+                call.success(),
+                return_delta0,
+                return_delta1,
+                lp_fee as i64,
+                fee_overridden,
+            ]);
+        }
+    }
+
+    Ok(())
+}
+
+/// Decodes the delta a hook returned to the PoolManager as its two packed int128 halves:
+/// `(amount0, amount1)` for `BalanceDelta` returned by the liquidity hooks,
+/// `(specified, unspecified)` for `BeforeSwapDelta`, and `(0, unspecified)` for
+/// `afterSwap`. `beforeSwap` also returns the LP fee override.
+fn decode_returns(hook_call: &IHooks::IHooksCalls, output: &[u8]) -> (i128, i128, u32) {
+    let decoded = match hook_call {
+        IHooks::IHooksCalls::afterAddLiquidity(_) => IHooks::afterAddLiquidityCall::abi_decode_returns(output)
+            .map(|r| { let (d0, d1) = unpack_delta(r._1); (d0, d1, 0) }),
+        IHooks::IHooksCalls::afterRemoveLiquidity(_) => IHooks::afterRemoveLiquidityCall::abi_decode_returns(output)
+            .map(|r| { let (d0, d1) = unpack_delta(r._1); (d0, d1, 0) }),
+        IHooks::IHooksCalls::beforeSwap(_) => IHooks::beforeSwapCall::abi_decode_returns(output)
+            .map(|r| { let (d0, d1) = unpack_delta(r._1); (d0, d1, r._2.to::<u32>()) }),
+        IHooks::IHooksCalls::afterSwap(_) => IHooks::afterSwapCall::abi_decode_returns(output)
+            .map(|r| (0, r._1, 0)),
+        _ => return (0, 0, 0),
+    };
+
+    decoded.unwrap_or_else(|e| {
+        debug!("Failed to decode univ4 hook call return data: {:?}", e);
+        (0, 0, 0)
+    })
+}
+
+/// Splits the LP fee returned by `beforeSwap` into its fee bits and its override flag.
+fn split_fee_override(lp_fee_override: u32) -> (u32, bool) {
+    (lp_fee_override & LP_FEE_MASK, lp_fee_override & OVERRIDE_FEE_FLAG != 0)
+}
+
+fn unpack_delta(delta: I256) -> (i128, i128) {
+    let raw = delta.into_raw();
+    let upper = (raw >> 128).to::<u128>() as i128;
+    let lower = (raw & U256::from(u128::MAX)).to::<u128>() as i128;
+    (upper, lower)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fee_override_is_split_from_its_flag() {
+        assert_eq!(split_fee_override(0), (0, false));
+        assert_eq!(split_fee_override(3000), (3000, false));
+        assert_eq!(split_fee_override(OVERRIDE_FEE_FLAG | 3000), (3000, true));
+        assert_eq!(split_fee_override(OVERRIDE_FEE_FLAG), (0, true));
+        // The dynamic fee flag (0x800000) is not an override flag and is dropped with it
+        assert_eq!(split_fee_override(0x800000 | 500), (500, false));
+    }
+
+    #[test]
+    fn deltas_unpack_into_signed_halves() {
+        let pack = |upper: i128, lower: i128| {
+            I256::from_raw((U256::from(upper as u128) << 128) | U256::from(lower as u128))
+        };
+
+        assert_eq!(unpack_delta(pack(0, 0)), (0, 0));
+        assert_eq!(unpack_delta(pack(1_000, -250)), (1_000, -250));
+        assert_eq!(unpack_delta(pack(-1, 7)), (-1, 7));
+        assert_eq!(unpack_delta(pack(i128::MIN, i128::MAX)), (i128::MIN, i128::MAX));
+    }
+}
@@ -0,0 +1,121 @@
+use crate::values;
+use crate::abi::{decode_log, format_value, sources as abi_sources};
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::reader::{abi_contracts, hook_addresses, remember_abi_contract, remember_hook};
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use alloy::{sol, sol_types::SolEvent, primitives::{address, hex, Address, TxHash}};
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use std::collections::HashMap;
+use tracing::debug;
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const CHAIN_ID: u32 = 1;
+
+sol! {
+    event Initialize(
+        bytes32 indexed id,
+        address indexed currency0,
+        address indexed currency1,
+        uint24 fee,
+        int24 tickSpacing,
+        address hooks,
+        uint160 sqrtPriceX96,
+        int24 tick
+    );
+}
+
+/// Writes one row per log emitted by a hook contract, i.e. an address some pool was
+/// initialized with, with its raw topics and data. Logs of hooks listed in an ABI source of
+/// `INDEXER_ABI_CONFIG`, or discovered through its factory, also carry the event name and a
+/// JSON object of its decoded inputs.
+/// A hook is watched from its first `Initialize` log onwards.
+pub async fn process_uni_v4_hook_logs(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let mut hooks = hook_addresses(&components.client).await?;
+
+    let mut logs: Vec<(usize, TxHash, usize, &alloy::primitives::Log)> = Vec::new();
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            for source in abi_sources() {
+                if let Some(contract) = source.discovered_contract(log.address, log.topics(), &log.data.data) {
+                    remember_abi_contract(&source.name, contract);
+                }
+            }
+
+            if log.address == UNIV4_FACTORY_CONTRACT_ADDRESS {
+                if log.topics().get(0) != Some(&Initialize::SIGNATURE_HASH) { continue; }
+                match Initialize::decode_raw_log(log.topics(), &log.data.data) {
+                    Ok(evt) if evt.hooks != Address::ZERO => {
+                        remember_hook(evt.hooks);
+                        hooks.insert(evt.hooks);
+                    }
+                    Ok(_) => {}
+                    Err(e) => { debug!("Failed to decode univ4 pool creation event: {:?}", e); }
+                }
+                continue;
+            }
+
+            if hooks.contains(&log.address) { logs.push((tx_idx, *tx.hash(), log_idx, log)); }
+        }
+    }
+    if logs.is_empty() { return Ok(()); }
+
+    let emitters: Vec<Address> = logs.iter().map(|(_, _, _, log)| log.address).collect();
+    let mut discovered_by: HashMap<Address, Vec<&str>> = HashMap::new();
+    for source in abi_sources().iter().filter(|source| source.factory.is_some()) {
+        for contract in abi_contracts(&components.client, &source.name, &emitters).await? {
+            discovered_by.entry(contract).or_default().push(&source.name);
+        }
+    }
+
+    for (tx_idx, tx_hash, log_idx, log) in logs {
+        let topic = |i: usize| log.topics().get(i).map(|t| t.to_string()).unwrap_or_default();
+        let sources = discovered_by.get(&log.address).map(Vec::as_slice).unwrap_or_default();
+        let (event_name, decoded_args) = match decode_log(log.address, sources, log.topics(), &log.data.data) {
+            Some((event, values)) => {
+                let args: serde_json::Map<String, serde_json::Value> = event
+                    .inputs
+                    .iter()
+                    .zip(values.iter())
+                    .enumerate()
+                    .map(|(i, (input, value))| {
+                        let name = if input.name.is_empty() { format!("arg{}", i) } else { input.name.clone() };
+                        (name, serde_json::Value::String(format_value(value)))
+                    })
+                    .collect();
+                (event.name.clone(), serde_json::Value::Object(args).to_string())
+            }
+            None => (String::new(), String::new()),
+        };
+
+        let event_id = format!("{}#{}", tx_hash, log_idx);
+        writer.write_record(values![
+            CHAIN_ID as i64,
+            block_number as i64,
+            block_timestamp,
+            tx_hash,
+            tx_idx as i64,
+            log_idx as i64,
+            log.address,
+            event_id,
+            topic(0),
+            topic(1),
+            topic(2),
+            topic(3),
+            hex::encode_prefixed(&log.data.data),
+            event_name,
+            decoded_args,
+        ]);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use crate::values;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::reader::latest_rows_before;
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use crate::utils::HookPermissions;
+use alloy::{sol, sol_types::SolEvent, primitives::{address, Address}};
+use eyre::Result;
+use chrono::{DateTime, Utc, TimeZone};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::debug;
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const CHAIN_ID: u32 = 1;
+
+sol! {
+    event Initialize(
+        bytes32 indexed id,
+        address indexed currency0,
+        address indexed currency1,
+        uint24 fee,
+        int24 tickSpacing,
+        address hooks,
+        uint160 sqrtPriceX96,
+        int24 tick
+    );
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct HookState {
+    hook_address: String,
+    first_seen_block: u64,
+    first_seen_timestamp: i64,
+    pool_count: u64,
+}
+
+/// Writes one row per pool initialized with a hook, carrying the hook's first-seen block
+/// and the number of pools using it so far. The latest row per `hook_address` is the
+/// current state of the hook.
+pub async fn process_uni_v4_hooks(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let mut events = Vec::new();
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+            if log.topics().get(0) != Some(&Initialize::SIGNATURE_HASH) { continue; }
+
+            match Initialize::decode_raw_log(log.topics(), &log.data.data) {
+                Ok(evt) if evt.hooks != Address::ZERO => events.push((tx_idx, tx.hash(), log_idx, log.address, evt)),
+                Ok(_) => {}
+                Err(e) => { debug!("Failed to decode univ4 pool creation event: {:?}", e); }
+            }
+        }
+    }
+
+    if events.is_empty() { return Ok(()); }
+
+    let hooks: Vec<Address> = events.iter().map(|e| e.4.hooks).collect();
+    let mut state = load_hook_state(&components.client, block_number, &hooks).await?;
+
+    for (tx_idx, tx_hash, log_idx, log_address, evt) in events {
+        let (first_seen_block, first_seen_timestamp, pool_count) = state
+            .entry(evt.hooks)
+            .or_insert((block_number, block_timestamp, 0));
+        *pool_count += 1;
+
+        let permissions = HookPermissions::from_address(evt.hooks);
+        let event_id = format!("{}#{}", tx_hash, log_idx);
+        writer.write_record(values![
+            CHAIN_ID as i64,
+            block_number as i64,
+            block_timestamp,
+            tx_hash,
+            tx_idx as i64,
+            log_idx as i64,
+            log_address,
+            event_id,
+            evt.hooks,
+            evt.id,
+            *first_seen_block as i64,
+            *first_seen_timestamp,
+            *pool_count as i64,
+            HookPermissions::bits(evt.hooks) as i64,
+            permissions.before_initialize,
+            permissions.after_initialize,
+            permissions.before_add_liquidity,
+            permissions.after_add_liquidity,
+            permissions.before_remove_liquidity,
+            permissions.after_remove_liquidity,
+            permissions.before_swap,
+            permissions.after_swap,
+            permissions.before_donate,
+            permissions.after_donate,
+            permissions.before_swap_return_delta,
+            permissions.after_swap_return_delta,
+            permissions.after_add_liquidity_return_delta,
+            permissions.after_remove_liquidity_return_delta,
+        ]);
+    }
+
+    Ok(())
+}
+
+async fn load_hook_state(
+    client: &clickhouse::Client,
+    block_number: u64,
+    hooks: &[Address],
+) -> Result<HashMap<Address, (u64, DateTime<Utc>, u64)>> {
+    let rows: Vec<HookState> = latest_rows_before(
+        client,
+        "uni_v4_hooks",
+        "hook_address, first_seen_block, toInt64(toUnixTimestamp(first_seen_timestamp)) AS first_seen_timestamp, \
+         pool_count",
+        &[("hook_address", hooks.iter().map(|hook| hook.to_checksum(Some(1))).collect())],
+        block_number,
+    )
+    .await?;
+
+    let mut state = HashMap::with_capacity(rows.len());
+    for row in rows {
+        let Ok(hook) = row.hook_address.parse::<Address>() else { continue; };
+        let first_seen_timestamp = Utc.timestamp_opt(row.first_seen_timestamp, 0).single().unwrap_or_default();
+        state.insert(hook, (row.first_seen_block, first_seen_timestamp, row.pool_count));
+    }
+    Ok(state)
+}
@@ -0,0 +1,199 @@
+use crate::values;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use crate::utils::position_key;
+use alloy::{sol, sol_types::SolEvent, primitives::{address, Address, FixedBytes, TxHash, U256}};
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use tracing::debug;
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const CHAIN_ID: u32 = 1;
+const PIPS_DENOMINATOR: u64 = 1_000_000;
+
+sol! {
+    event Swap(
+        bytes32 indexed id,
+        address indexed sender,
+        int128 amount0,
+        int128 amount1,
+        uint160 sqrtPriceX96,
+        uint128 liquidity,
+        int24 tick,
+        uint24 fee
+    );
+    event ModifyLiquidity(
+        bytes32 indexed id,
+        address indexed sender,
+        int24 tickLower,
+        int24 tickUpper,
+        int256 liquidityDelta,
+        bytes32 salt
+    );
+}
+
+enum PoolEvent {
+    Liquidity { position_id: FixedBytes<32>, evt: ModifyLiquidity, liquidity_delta: i128 },
+    Swap(Swap),
+}
+
+struct BlockEvent {
+    tx_idx: usize,
+    tx_hash: TxHash,
+    log_idx: usize,
+    origin: Address,
+    event: PoolEvent,
+}
+
+/// Writes one row per just-in-time liquidity episode in a block: liquidity added to a
+/// position, one or more swaps from other transactions on the same pool with the pool
+/// tick inside the position's range, then liquidity removed from the same position. The
+/// fee capture estimate charges each victim swap's fee on its input and attributes the
+/// share `added liquidity / swap liquidity`, ignoring the protocol fee.
+pub async fn process_uni_v4_jit_liquidity(
+    block_data: &EthereumBlockData,
+    _components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let mut events = Vec::new();
+    let transactions = block.body().transactions.iter().zip(block.senders().iter());
+    for (tx_idx, ((tx, origin), receipt)) in transactions.zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+
+            let decoded = match log.topics().get(0) {
+                Some(topic) if *topic == Swap::SIGNATURE_HASH => {
+                    Swap::decode_raw_log(log.topics(), &log.data.data).map(PoolEvent::Swap)
+                }
+                Some(topic) if *topic == ModifyLiquidity::SIGNATURE_HASH => {
+                    ModifyLiquidity::decode_raw_log(log.topics(), &log.data.data).map(|evt| {
+                        let position_id = position_key(evt.sender, evt.tickLower.as_i32(), evt.tickUpper.as_i32(), evt.salt);
+                        let liquidity_delta = i128::try_from(evt.liquidityDelta).unwrap_or_default();
+                        PoolEvent::Liquidity { position_id, evt, liquidity_delta }
+                    })
+                }
+                _ => continue,
+            };
+
+            match decoded {
+                Ok(event) => events.push(BlockEvent { tx_idx, tx_hash: *tx.hash(), log_idx, origin: *origin, event }),
+                Err(e) => { debug!("Failed to decode univ4 jit liquidity event: {:?}", e); }
+            }
+        }
+    }
+
+    let mut removed = vec![false; events.len()];
+    for (add_idx, add) in events.iter().enumerate() {
+        let PoolEvent::Liquidity { position_id, evt: add_evt, liquidity_delta } = &add.event else { continue; };
+        if *liquidity_delta <= 0 { continue; }
+
+        let remove_idx = (add_idx + 1..events.len()).find(|&i| !removed[i] && matches!(
+            &events[i].event,
+            PoolEvent::Liquidity { position_id: id, liquidity_delta: delta, .. } if id == position_id && *delta < 0
+        ));
+        let Some(remove_idx) = remove_idx else { continue; };
+        let remove = &events[remove_idx];
+
+        let (tick_lower, tick_upper) = (add_evt.tickLower.as_i32(), add_evt.tickUpper.as_i32());
+        let victims: Vec<(&BlockEvent, &Swap)> = events[add_idx + 1..remove_idx]
+            .iter()
+            .filter(|e| e.tx_idx != add.tx_idx && e.tx_idx != remove.tx_idx)
+            .filter_map(|e| match &e.event { PoolEvent::Swap(swap) => Some((e, swap)), _ => None })
+            .filter(|(_, swap)| swap.id == add_evt.id)
+            .filter(|(_, swap)| tick_lower <= swap.tick.as_i32() && swap.tick.as_i32() < tick_upper)
+            .collect();
+        if victims.is_empty() { continue; }
+        removed[remove_idx] = true;
+
+        let (fee_amount0, fee_amount1) = estimate_fee_capture(victims.iter().map(|(_, swap)| *swap), liquidity_delta.unsigned_abs());
+
+        let victim_hashes: Vec<String> = victims.iter().map(|(e, _)| e.tx_hash.to_string()).collect();
+        let event_id = format!("{}#{}", add.tx_hash, add.log_idx);
+        writer.write_record(values![
+            CHAIN_ID as i64,
+            block_number as i64,
+            block_timestamp,
+            event_id,
+            add_evt.id,
+            position_id,
+            add.origin,
+            add_evt.sender,
+            tick_lower,
+            tick_upper,
+            *liquidity_delta,
+            add.tx_hash,
+            add.tx_idx as i64,
+            add.log_idx as i64,
+            remove.tx_hash,
+            remove.tx_idx as i64,
+            remove.log_idx as i64,
+            victims.len() as i64,
+            victim_hashes.join(","),
+            fee_amount0,
+            fee_amount1,
+        ]);
+    }
+
+    Ok(())
+}
+
+/// Fees earned by `liquidity` over `swaps`, as `(amount0, amount1)`: each swap's fee is
+/// charged on its input and shared by `liquidity / swap liquidity`.
+fn estimate_fee_capture<'a>(swaps: impl IntoIterator<Item = &'a Swap>, liquidity: u128) -> (u128, u128) {
+    let (mut fee_amount0, mut fee_amount1) = (0u128, 0u128);
+    for swap in swaps {
+        if swap.liquidity == 0 { continue; }
+        let zero_for_one = swap.amount0 < 0;
+        let amount_in = if zero_for_one { swap.amount0.unsigned_abs() } else { swap.amount1.unsigned_abs() };
+        let share = U256::from(amount_in) * U256::from(swap.fee.to::<u64>()) * U256::from(liquidity)
+            / U256::from(PIPS_DENOMINATOR)
+            / U256::from(swap.liquidity);
+        let share = share.saturating_to::<u128>();
+        if zero_for_one {
+            fee_amount0 = fee_amount0.saturating_add(share);
+        } else {
+            fee_amount1 = fee_amount1.saturating_add(share);
+        }
+    }
+    (fee_amount0, fee_amount1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::aliases::{I24, U160, U24};
+
+    fn swap(amount0: i128, amount1: i128, liquidity: u128, fee: u32) -> Swap {
+        Swap {
+            id: FixedBytes::repeat_byte(1),
+            sender: Address::ZERO,
+            amount0,
+            amount1,
+            sqrtPriceX96: U160::ZERO,
+            liquidity,
+            tick: I24::ZERO,
+            fee: U24::from(fee),
+        }
+    }
+
+    #[test]
+    fn fee_capture_is_shared_by_liquidity_on_the_input_side() {
+        let swaps = [
+            // 0.30% of 1,000,000 token0 in, 75% of the liquidity
+            swap(-1_000_000, 990_000, 1_000_000, 3000),
+            // 0.05% of 2,000,000 token1 in, 75% of the liquidity
+            swap(1_990_000, -2_000_000, 1_000_000, 500),
+            // Swaps without in-range liquidity earn nothing
+            swap(-1_000_000, 0, 0, 3000),
+        ];
+        assert_eq!(estimate_fee_capture(&swaps, 750_000), (2250, 750));
+        assert_eq!(estimate_fee_capture(&swaps, 1_000_000), (3000, 1000));
+        assert_eq!(estimate_fee_capture(&swaps, 0), (0, 0));
+        assert_eq!(estimate_fee_capture(&swaps[..0], 750_000), (0, 0));
+    }
+}
@@ -2,3 +2,24 @@ pub mod pools;
 pub mod swaps;
 pub mod modify_liquidity;
 pub mod donations;
+pub mod ticks;
+pub mod positions;
+pub mod position_nfts;
+pub mod hooks;
+pub mod hook_calls;
+pub mod claims;
+pub mod protocol_fees;
+pub mod fee_changes;
+pub mod tokens;
+pub mod swaps_enriched;
+pub mod fees;
+pub mod sandwiches;
+pub mod jit_liquidity;
+pub mod transactions;
+pub mod routes;
+pub mod settlements;
+pub mod hook_logs;
+pub mod raw_logs;
+pub mod v3;
+pub mod v2;
+pub mod abi_events;
@@ -2,10 +2,8 @@ use crate::values;
 use crate::indexer::{ProcessingComponents, EthereumBlockData};
 use crate::storage::writer::ClickhouseWriter as DbWriter;
 use alloy::{sol, sol_types::SolEvent, primitives::{address, Address}};
-use reth_node_api::FullNodeComponents;
 use eyre::Result;
 use chrono::{Utc, TimeZone};
-use reth_rpc_eth_api::helpers::FullEthApi;
 use tracing::debug;
 
 const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
@@ -22,9 +20,9 @@ sol! {
     );
 }
 
-pub async fn process_uni_v4_modify_liquidity<Node: FullNodeComponents, EthApi: FullEthApi>(
+pub async fn process_uni_v4_modify_liquidity(
     block_data: &EthereumBlockData,
-    _components: ProcessingComponents<Node, EthApi>,
+    _components: ProcessingComponents,
     writer: &mut DbWriter,
 ) -> Result<()> {
     let block = &block_data.0;
@@ -1,11 +1,10 @@
 use crate::values;
 use crate::indexer::{ProcessingComponents, EthereumBlockData};
 use crate::storage::writer::ClickhouseWriter as DbWriter;
+use crate::utils::{HookPermissions, DYNAMIC_FEE_FLAG};
 use alloy::{sol, sol_types::SolEvent, primitives::{address, Address}};
-use reth_node_api::FullNodeComponents;
 use eyre::Result;
 use chrono::{Utc, TimeZone};
-use reth_rpc_eth_api::helpers::FullEthApi;
 use tracing::debug;
 
 const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
@@ -48,9 +47,9 @@ sol! {
     );
 }
 
-pub async fn process_uni_v4_pools<Node: FullNodeComponents, EthApi: FullEthApi>(
+pub async fn process_uni_v4_pools(
     block_data: &EthereumBlockData,
-    _components: ProcessingComponents<Node, EthApi>,
+    _components: ProcessingComponents,
     writer: &mut DbWriter,
 ) -> Result<()> {
     let block = &block_data.0;
@@ -65,6 +64,7 @@ pub async fn process_uni_v4_pools<Node: FullNodeComponents, EthApi: FullEthApi>(
             if log.topics().get(0) == Some(&Initialize::SIGNATURE_HASH) {
                 match Initialize::decode_raw_log(log.topics(), &log.data.data) {
                     Ok(create) => {
+                        let permissions = HookPermissions::from_address(create.hooks);
                         writer
                             .write_record(values![
                                 CHAIN_ID as i64,
@@ -82,6 +82,21 @@ pub async fn process_uni_v4_pools<Node: FullNodeComponents, EthApi: FullEthApi>(
                                 create.hooks,
                                 create.sqrtPriceX96,
                                 create.tick,
+                                permissions.before_initialize,
+                                permissions.after_initialize,
+                                permissions.before_add_liquidity,
+                                permissions.after_add_liquidity,
+                                permissions.before_remove_liquidity,
+                                permissions.after_remove_liquidity,
+                                permissions.before_swap,
+                                permissions.after_swap,
+                                permissions.before_donate,
+                                permissions.after_donate,
+                                permissions.before_swap_return_delta,
+                                permissions.after_swap_return_delta,
+                                permissions.after_add_liquidity_return_delta,
+                                permissions.after_remove_liquidity_return_delta,
+                                create.fee.to::<u32>() == DYNAMIC_FEE_FLAG,
                             ]);
                     }
                     Err(e) => { debug!("Failed to decode univ4 pool creation event: {:?}", e); }
@@ -0,0 +1,249 @@
+use crate::values;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::reader::latest_rows_before;
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use crate::utils::position_key;
+use alloy::{sol, sol_types::SolEvent, primitives::{address, Address, FixedBytes, U256}};
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::debug;
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const UNIV4_POSITION_MANAGER_ADDRESS: Address = address!("0xbD216513d74C8cf14cf4747E6AaA6420FF64ee9e");
+const CHAIN_ID: u32 = 1;
+
+sol! {
+    event Transfer(address indexed from, address indexed to, uint256 indexed id);
+    event Subscription(uint256 indexed tokenId, address indexed subscriber);
+    event Unsubscription(uint256 indexed tokenId, address indexed subscriber);
+    event ModifyLiquidity(
+        bytes32 indexed id,
+        address indexed sender,
+        int24 tickLower,
+        int24 tickUpper,
+        int256 liquidityDelta,
+        bytes32 salt
+    );
+}
+
+/// Latest known owner, subscriber and position of a PositionManager token.
+#[derive(Debug, Clone, Default)]
+struct TokenState {
+    owner: Address,
+    subscriber: Address,
+    pool_id: FixedBytes<32>,
+    position_id: FixedBytes<32>,
+    tick_lower: i32,
+    tick_upper: i32,
+}
+
+impl TokenState {
+    /// Links the token to the position it was minted or modified in this block, if any,
+    /// from the `ModifyLiquidity` events by salt.
+    fn link_position(&mut self, token_id: U256, positions: &HashMap<FixedBytes<32>, (FixedBytes<32>, i32, i32)>) {
+        let salt = FixedBytes::<32>::from(token_id.to_be_bytes::<32>());
+        if let Some((pool_id, tick_lower, tick_upper)) = positions.get(&salt) {
+            self.pool_id = *pool_id;
+            self.tick_lower = *tick_lower;
+            self.tick_upper = *tick_upper;
+            self.position_id = position_key(UNIV4_POSITION_MANAGER_ADDRESS, *tick_lower, *tick_upper, salt);
+        }
+    }
+
+    /// Applies `event` to the token, returning its event type and the from and to
+    /// addresses of its row.
+    fn apply(&mut self, event: NftEvent) -> (&'static str, Address, Address) {
+        match event {
+            NftEvent::Transfer { from, to } => {
+                self.owner = to;
+                let event_type = if from == Address::ZERO { "mint" } else if to == Address::ZERO { "burn" } else { "transfer" };
+                (event_type, from, to)
+            }
+            NftEvent::Subscription(subscriber) => {
+                self.subscriber = subscriber;
+                ("subscription", Address::ZERO, subscriber)
+            }
+            NftEvent::Unsubscription(subscriber) => {
+                self.subscriber = Address::ZERO;
+                ("unsubscription", subscriber, Address::ZERO)
+            }
+        }
+    }
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct TokenStateRow {
+    token_id: String,
+    owner: String,
+    subscriber: String,
+    pool_id: String,
+    position_id: String,
+    tick_lower: i32,
+    tick_upper: i32,
+}
+
+/// Writes one row per PositionManager `Transfer`, `Subscription` and `Unsubscription`
+/// event with the token's owner and subscriber after the event. Positions minted through
+/// the PositionManager use the token id as `salt`, so the PoolManager position id is
+/// `keccak256(PositionManager, tickLower, tickUpper, tokenId)` and joins to
+/// `uni_v4_positions.position_id`.
+pub async fn process_uni_v4_position_nfts(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let mut positions: HashMap<FixedBytes<32>, (FixedBytes<32>, i32, i32)> = HashMap::new();
+    let mut events = Vec::new();
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            let topic0 = log.topics().get(0);
+
+            if log.address == UNIV4_FACTORY_CONTRACT_ADDRESS && topic0 == Some(&ModifyLiquidity::SIGNATURE_HASH) {
+                match ModifyLiquidity::decode_raw_log(log.topics(), &log.data.data) {
+                    Ok(evt) if evt.sender == UNIV4_POSITION_MANAGER_ADDRESS => {
+                        positions.insert(evt.salt, (evt.id, evt.tickLower.as_i32(), evt.tickUpper.as_i32()));
+                    }
+                    Ok(_) => {}
+                    Err(e) => { debug!("Failed to decode univ4 modify liquidity event: {:?}", e); }
+                }
+                continue;
+            }
+
+            if log.address != UNIV4_POSITION_MANAGER_ADDRESS { continue; }
+
+            let decoded = if topic0 == Some(&Transfer::SIGNATURE_HASH) {
+                Transfer::decode_raw_log(log.topics(), &log.data.data)
+                    .map(|evt| (evt.id, NftEvent::Transfer { from: evt.from, to: evt.to }))
+            } else if topic0 == Some(&Subscription::SIGNATURE_HASH) {
+                Subscription::decode_raw_log(log.topics(), &log.data.data)
+                    .map(|evt| (evt.tokenId, NftEvent::Subscription(evt.subscriber)))
+            } else if topic0 == Some(&Unsubscription::SIGNATURE_HASH) {
+                Unsubscription::decode_raw_log(log.topics(), &log.data.data)
+                    .map(|evt| (evt.tokenId, NftEvent::Unsubscription(evt.subscriber)))
+            } else {
+                continue;
+            };
+
+            match decoded {
+                Ok((token_id, event)) => events.push((tx_idx, tx.hash(), log_idx, log.address, token_id, event)),
+                Err(e) => { debug!("Failed to decode univ4 position manager event: {:?}", e); }
+            }
+        }
+    }
+
+    if events.is_empty() { return Ok(()); }
+
+    let token_ids: Vec<U256> = events.iter().map(|e| e.4).collect();
+    let mut state = load_token_state(&components.client, block_number, &token_ids).await?;
+
+    for (tx_idx, tx_hash, log_idx, log_address, token_id, event) in events {
+        let token = state.entry(token_id).or_default();
+
+        token.link_position(token_id, &positions);
+        let (event_type, from, to) = token.apply(event);
+
+        let event_id = format!("{}#{}", tx_hash, log_idx);
+        writer.write_record(values![
+            CHAIN_ID as i64,
+            block_number as i64,
+            block_timestamp,
+            tx_hash,
+            tx_idx as i64,
+            log_idx as i64,
+            log_address,
+            event_id,
+            event_type,
+            token_id,
+            from,
+            to,
+            token.owner,
+            token.subscriber,
+            token.pool_id,
+            token.position_id,
+            token.tick_lower,
+            token.tick_upper,
+        ]);
+    }
+
+    Ok(())
+}
+
+enum NftEvent {
+    Transfer { from: Address, to: Address },
+    Subscription(Address),
+    Unsubscription(Address),
+}
+
+async fn load_token_state(
+    client: &clickhouse::Client,
+    block_number: u64,
+    token_ids: &[U256],
+) -> Result<HashMap<U256, TokenState>> {
+    let rows: Vec<TokenStateRow> = latest_rows_before(
+        client,
+        "uni_v4_position_nfts",
+        "toString(token_id) AS token_id, owner, subscriber, toString(pool_id) AS pool_id, \
+         toString(position_id) AS position_id, tick_lower, tick_upper",
+        &[("token_id", token_ids.iter().map(|id| id.to_string()).collect())],
+        block_number,
+    )
+    .await?;
+
+    let mut state = HashMap::with_capacity(rows.len());
+    for row in rows {
+        let Ok(token_id) = row.token_id.parse::<U256>() else { continue; };
+        state.insert(token_id, TokenState {
+            owner: row.owner.parse().unwrap_or_default(),
+            subscriber: row.subscriber.parse().unwrap_or_default(),
+            pool_id: row.pool_id.parse().unwrap_or_default(),
+            position_id: row.position_id.parse().unwrap_or_default(),
+            tick_lower: row.tick_lower,
+            tick_upper: row.tick_upper,
+        });
+    }
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_link_to_their_position_and_follow_events() {
+        let token_id = U256::from(42);
+        let salt = FixedBytes::<32>::from(token_id.to_be_bytes::<32>());
+        let pool_id = FixedBytes::repeat_byte(7);
+        let owner = Address::repeat_byte(1);
+        let buyer = Address::repeat_byte(2);
+        let subscriber = Address::repeat_byte(3);
+
+        let mut token = TokenState::default();
+        token.link_position(token_id, &HashMap::from([(salt, (pool_id, -600, 600))]));
+        assert_eq!(token.apply(NftEvent::Transfer { from: Address::ZERO, to: owner }), ("mint", Address::ZERO, owner));
+        assert_eq!(token.pool_id, pool_id);
+        assert_eq!((token.tick_lower, token.tick_upper), (-600, 600));
+        assert_eq!(token.position_id, position_key(UNIV4_POSITION_MANAGER_ADDRESS, -600, 600, salt));
+
+        // Positions of other tokens in the block don't touch the link
+        let other_salt = FixedBytes::<32>::from(U256::from(43).to_be_bytes::<32>());
+        token.link_position(token_id, &HashMap::from([(other_salt, (FixedBytes::repeat_byte(8), 0, 60))]));
+        assert_eq!(token.pool_id, pool_id);
+
+        assert_eq!(token.apply(NftEvent::Subscription(subscriber)), ("subscription", Address::ZERO, subscriber));
+        assert_eq!(token.subscriber, subscriber);
+        assert_eq!(token.apply(NftEvent::Transfer { from: owner, to: buyer }), ("transfer", owner, buyer));
+        assert_eq!(token.owner, buyer);
+        assert_eq!(token.apply(NftEvent::Unsubscription(subscriber)), ("unsubscription", subscriber, Address::ZERO));
+        assert_eq!(token.subscriber, Address::ZERO);
+        assert_eq!(token.apply(NftEvent::Transfer { from: buyer, to: Address::ZERO }), ("burn", buyer, Address::ZERO));
+        assert_eq!(token.owner, Address::ZERO);
+        assert_eq!(token.position_id, position_key(UNIV4_POSITION_MANAGER_ADDRESS, -600, 600, salt));
+    }
+}
@@ -0,0 +1,134 @@
+use crate::values;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::reader::latest_rows_before;
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use crate::utils::position_key;
+use alloy::{sol, sol_types::SolEvent, primitives::{address, Address, FixedBytes}};
+use eyre::Result;
+use chrono::{DateTime, Utc, TimeZone};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::debug;
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const CHAIN_ID: u32 = 1;
+
+sol! {
+    event ModifyLiquidity(
+        bytes32 indexed id,
+        address indexed sender,
+        int24 tickLower,
+        int24 tickUpper,
+        int256 liquidityDelta,
+        bytes32 salt
+    );
+}
+
+/// Latest known state of a position, as stored in `uni_v4_positions`.
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct PositionState {
+    position_id: String,
+    liquidity: String,
+    opened_at: i64,
+}
+
+/// Writes one row per `ModifyLiquidity` event keyed by the PoolManager position key
+/// (`keccak256(owner, tickLower, tickUpper, salt)`), carrying the position's liquidity
+/// after the change. `opened_at` is when the liquidity last went from zero to non-zero,
+/// `closed_at` when it went back to zero (epoch while the position is open).
+pub async fn process_uni_v4_positions(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let mut events = Vec::new();
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+            if log.topics().get(0) != Some(&ModifyLiquidity::SIGNATURE_HASH) { continue; }
+
+            match ModifyLiquidity::decode_raw_log(log.topics(), &log.data.data) {
+                Ok(evt) => {
+                    let position_id = position_key(evt.sender, evt.tickLower.as_i32(), evt.tickUpper.as_i32(), evt.salt);
+                    events.push((tx_idx, tx.hash(), log_idx, log.address, position_id, evt));
+                }
+                Err(e) => { debug!("Failed to decode univ4 modify liquidity event: {:?}", e); }
+            }
+        }
+    }
+
+    if events.is_empty() { return Ok(()); }
+
+    let position_ids: Vec<FixedBytes<32>> = events.iter().map(|e| e.4).collect();
+    let mut state = load_position_state(&components.client, block_number, &position_ids).await?;
+    let epoch = Utc.timestamp_opt(0, 0).unwrap();
+
+    for (tx_idx, tx_hash, log_idx, log_address, position_id, evt) in events {
+        let liquidity_delta: i128 = match evt.liquidityDelta.try_into() {
+            Ok(delta) => delta,
+            Err(e) => { debug!("Liquidity delta out of range for {}#{}: {:?}", tx_hash, log_idx, e); continue; }
+        };
+
+        let (liquidity, opened_at) = state.entry(position_id).or_insert((0, epoch));
+        let was_open = *liquidity > 0;
+        *liquidity = liquidity.saturating_add_signed(liquidity_delta);
+        let is_open = *liquidity > 0;
+        if is_open && !was_open { *opened_at = block_timestamp; }
+        let closed_at = if was_open && !is_open { block_timestamp } else { epoch };
+
+        let event_id = format!("{}#{}", tx_hash, log_idx);
+        writer.write_record(values![
+            CHAIN_ID as i64,
+            block_number as i64,
+            block_timestamp,
+            tx_hash,
+            tx_idx as i64,
+            log_idx as i64,
+            log_address,
+            event_id,
+            evt.id,
+            position_id,
+            evt.sender,
+            evt.tickLower,
+            evt.tickUpper,
+            evt.salt,
+            liquidity_delta,
+            *liquidity,
+            is_open,
+            *opened_at,
+            closed_at,
+        ]);
+    }
+
+    Ok(())
+}
+
+async fn load_position_state(
+    client: &clickhouse::Client,
+    block_number: u64,
+    position_ids: &[FixedBytes<32>],
+) -> Result<HashMap<FixedBytes<32>, (u128, DateTime<Utc>)>> {
+    let rows: Vec<PositionState> = latest_rows_before(
+        client,
+        "uni_v4_positions",
+        "toString(position_id) AS position_id, toString(liquidity) AS liquidity, \
+         toInt64(toUnixTimestamp(opened_at)) AS opened_at",
+        &[("position_id", position_ids.iter().map(|id| id.to_string()).collect())],
+        block_number,
+    )
+    .await?;
+
+    let mut state = HashMap::with_capacity(rows.len());
+    for row in rows {
+        let Ok(position_id) = row.position_id.parse::<FixedBytes<32>>() else { continue; };
+        let liquidity = row.liquidity.parse::<u128>().unwrap_or_default();
+        let opened_at = Utc.timestamp_opt(row.opened_at, 0).single().unwrap_or_default();
+        state.insert(position_id, (liquidity, opened_at));
+    }
+    Ok(state)
+}
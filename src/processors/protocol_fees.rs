@@ -0,0 +1,120 @@
+use crate::values;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use alloy::{sol, sol_types::SolEvent, primitives::{address, Address}};
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use tracing::debug;
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const CHAIN_ID: u32 = 1;
+
+sol! {
+    event ProtocolFeeUpdated(bytes32 indexed id, uint24 protocolFee);
+    event ProtocolFeeControllerUpdated(address indexed protocolFeeController);
+}
+
+/// Writes one row per `ProtocolFeeUpdated` event. The packed uint24 is also split into its
+/// two 12-bit halves: the fee charged on zeroForOne swaps (lower bits) and on oneForZero
+/// swaps (upper bits), both in pips. Swaps join to the latest row per pool at or before
+/// their block.
+pub async fn process_uni_v4_protocol_fees(
+    block_data: &EthereumBlockData,
+    _components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+            if log.topics().get(0) != Some(&ProtocolFeeUpdated::SIGNATURE_HASH) { continue; }
+
+            match ProtocolFeeUpdated::decode_raw_log(log.topics(), &log.data.data) {
+                Ok(evt) => {
+                    let protocol_fee = evt.protocolFee.to::<u32>();
+                    let (zero_for_one_fee, one_for_zero_fee) = split_protocol_fee(protocol_fee);
+                    let event_id = format!("{}#{}", tx.hash(), log_idx);
+                    writer.write_record(values![
+                        CHAIN_ID as i64,
+                        block_number as i64,
+                        block_timestamp,
+                        tx.hash(),
+                        tx_idx as i64,
+                        log_idx as i64,
+                        log.address,
+                        event_id,
+                        evt.id,
+                        protocol_fee as i64,
+                        zero_for_one_fee as i64,
+                        one_for_zero_fee as i64,
+                    ]);
+                }
+                Err(e) => { debug!("Failed to decode univ4 protocol fee updated event: {:?}", e); }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Splits a packed protocol fee into its zeroForOne and oneForZero fees.
+fn split_protocol_fee(protocol_fee: u32) -> (u32, u32) {
+    (protocol_fee & 0xfff, protocol_fee >> 12)
+}
+
+pub async fn process_uni_v4_protocol_fee_controllers(
+    block_data: &EthereumBlockData,
+    _components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+            if log.topics().get(0) != Some(&ProtocolFeeControllerUpdated::SIGNATURE_HASH) { continue; }
+
+            match ProtocolFeeControllerUpdated::decode_raw_log(log.topics(), &log.data.data) {
+                Ok(evt) => {
+                    let event_id = format!("{}#{}", tx.hash(), log_idx);
+                    writer.write_record(values![
+                        CHAIN_ID as i64,
+                        block_number as i64,
+                        block_timestamp,
+                        tx.hash(),
+                        tx_idx as i64,
+                        log_idx as i64,
+                        log.address,
+                        event_id,
+                        evt.protocolFeeController,
+                    ]);
+                }
+                Err(e) => { debug!("Failed to decode univ4 protocol fee controller updated event: {:?}", e); }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protocol_fee_splits_into_directional_halves() {
+        assert_eq!(split_protocol_fee(0), (0, 0));
+        assert_eq!(split_protocol_fee(500), (500, 0));
+        assert_eq!(split_protocol_fee(1000 << 12), (0, 1000));
+        assert_eq!(split_protocol_fee((1000 << 12) | 500), (500, 1000));
+        // Both halves at the PoolManager's 0.1% cap
+        assert_eq!(split_protocol_fee(0x3e83e8), (1000, 1000));
+    }
+}
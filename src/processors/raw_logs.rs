@@ -0,0 +1,198 @@
+use crate::values;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use alloy::{sol, sol_types::{SolEvent, SolEventInterface}, primitives::{address, hex, Address, Log, B256}};
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use std::collections::BTreeMap;
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const CHAIN_ID: u32 = 1;
+
+sol! {
+    interface IPoolManager {
+        event Initialize(
+            bytes32 indexed id,
+            address indexed currency0,
+            address indexed currency1,
+            uint24 fee,
+            int24 tickSpacing,
+            address hooks,
+            uint160 sqrtPriceX96,
+            int24 tick
+        );
+        event Swap(
+            bytes32 indexed id,
+            address indexed sender,
+            int128 amount0,
+            int128 amount1,
+            uint160 sqrtPriceX96,
+            uint128 liquidity,
+            int24 tick,
+            uint24 fee
+        );
+        event ModifyLiquidity(
+            bytes32 indexed id,
+            address indexed sender,
+            int24 tickLower,
+            int24 tickUpper,
+            int256 liquidityDelta,
+            bytes32 salt
+        );
+        event Donate(bytes32 indexed id, address indexed sender, uint256 amount0, uint256 amount1);
+        event Transfer(address caller, address indexed from, address indexed to, uint256 indexed id, uint256 amount);
+        event Approval(address indexed owner, address indexed spender, uint256 indexed id, uint256 amount);
+        event OperatorSet(address indexed owner, address indexed operator, bool approved);
+        event ProtocolFeeUpdated(bytes32 indexed id, uint24 protocolFee);
+        event ProtocolFeeControllerUpdated(address indexed protocolFeeController);
+        event OwnershipTransferred(address indexed user, address indexed newOwner);
+    }
+}
+
+/// Every event the PoolManager emits, by signature hash.
+const POOL_MANAGER_EVENTS: [(B256, &str); 10] = [
+    (IPoolManager::Initialize::SIGNATURE_HASH, "Initialize"),
+    (IPoolManager::Swap::SIGNATURE_HASH, "Swap"),
+    (IPoolManager::ModifyLiquidity::SIGNATURE_HASH, "ModifyLiquidity"),
+    (IPoolManager::Donate::SIGNATURE_HASH, "Donate"),
+    (IPoolManager::Transfer::SIGNATURE_HASH, "Transfer"),
+    (IPoolManager::Approval::SIGNATURE_HASH, "Approval"),
+    (IPoolManager::OperatorSet::SIGNATURE_HASH, "OperatorSet"),
+    (IPoolManager::ProtocolFeeUpdated::SIGNATURE_HASH, "ProtocolFeeUpdated"),
+    (IPoolManager::ProtocolFeeControllerUpdated::SIGNATURE_HASH, "ProtocolFeeControllerUpdated"),
+    (IPoolManager::OwnershipTransferred::SIGNATURE_HASH, "OwnershipTransferred"),
+];
+
+/// How a PoolManager log decodes: the event its first topic names, if any, and whether the
+/// log decodes as that event.
+fn decode_status(log: &Log) -> (Option<&'static str>, bool) {
+    let Some(topic0) = log.topics().first() else { return (None, false); };
+    let Some(&(_, name)) = POOL_MANAGER_EVENTS.iter().find(|(hash, _)| hash == topic0) else { return (None, false); };
+    (Some(name), IPoolManager::IPoolManagerEvents::decode_raw_log(log.topics(), &log.data.data).is_ok())
+}
+
+/// Writes every log emitted by the PoolManager as-is (topics and data), with the event it
+/// was recognized as and whether it decoded, so derived tables can be rebuilt from it.
+pub async fn process_uni_v4_raw_logs(
+    block_data: &EthereumBlockData,
+    _components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+
+            let (event_name, decoded) = decode_status(log);
+            let topic = |i: usize| log.topics().get(i).map(|t| t.to_string()).unwrap_or_default();
+
+            let event_id = format!("{}#{}", tx.hash(), log_idx);
+            writer.write_record(values![
+                CHAIN_ID as i64,
+                block_number as i64,
+                block_timestamp,
+                tx.hash(),
+                tx_idx as i64,
+                log_idx as i64,
+                log.address,
+                event_id,
+                topic(0),
+                topic(1),
+                topic(2),
+                topic(3),
+                hex::encode_prefixed(&log.data.data),
+                event_name.unwrap_or_default(),
+                decoded,
+            ]);
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes one row per block and first topic for PoolManager logs that could not be decoded:
+/// `unknown_topic` when the topic is not a known PoolManager event, `decode_error` when the
+/// log does not decode as the event it names. Summing `failures` by `topic0` gives the
+/// failure counters.
+pub async fn process_uni_v4_decode_failures(
+    block_data: &EthereumBlockData,
+    _components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let mut failures: BTreeMap<(String, &'static str, &'static str), (u64, String)> = BTreeMap::new();
+    for (tx, receipt) in block.body().transactions.iter().zip(receipts.iter()) {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+
+            let (event_name, reason) = match decode_status(log) {
+                (Some(_), true) => continue,
+                (Some(name), false) => (name, "decode_error"),
+                (None, _) => ("", "unknown_topic"),
+            };
+            let topic0 = log.topics().first().map(|t| t.to_string()).unwrap_or_default();
+
+            failures
+                .entry((topic0, event_name, reason))
+                .or_insert_with(|| (0, format!("{}#{}", tx.hash(), log_idx)))
+                .0 += 1;
+        }
+    }
+
+    for ((topic0, event_name, reason), (count, first_event_id)) in failures {
+        writer.write_record(values![
+            CHAIN_ID as i64,
+            block_number as i64,
+            block_timestamp,
+            topic0,
+            event_name,
+            reason,
+            count as i64,
+            first_event_id,
+        ]);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{b256, Bytes, FixedBytes, LogData, U256};
+
+    fn log(data: LogData) -> Log {
+        Log { address: UNIV4_FACTORY_CONTRACT_ADDRESS, data }
+    }
+
+    #[test]
+    fn logs_are_named_by_topic_and_checked_against_their_event() {
+        assert_eq!(
+            IPoolManager::Swap::SIGNATURE_HASH,
+            b256!("0x40e9cecb9f5f1f1c5b9c97dec2917b7ee92e57ba5563708daca94dd84ad7112f")
+        );
+
+        let donate = IPoolManager::Donate {
+            id: FixedBytes::repeat_byte(1),
+            sender: Address::repeat_byte(2),
+            amount0: U256::from(10),
+            amount1: U256::from(20),
+        }
+        .encode_log_data();
+        assert_eq!(decode_status(&log(donate.clone())), (Some("Donate"), true));
+
+        let truncated = LogData::new_unchecked(donate.topics().to_vec(), Bytes::copy_from_slice(&donate.data[..32]));
+        assert_eq!(decode_status(&log(truncated)), (Some("Donate"), false));
+
+        let unknown = LogData::new_unchecked(vec![B256::repeat_byte(9)], Bytes::new());
+        assert_eq!(decode_status(&log(unknown)), (None, false));
+        assert_eq!(decode_status(&log(LogData::new_unchecked(vec![], Bytes::new()))), (None, false));
+    }
+}
@@ -0,0 +1,224 @@
+use crate::values;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::reader::{pool_currencies, remember_pool_currencies};
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use crate::traces::call_paths;
+use alloy::{sol, sol_types::{SolCall, SolEvent}, primitives::{address, Address, FixedBytes, TxHash}};
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use std::collections::HashMap;
+use tracing::debug;
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const CHAIN_ID: u32 = 1;
+
+sol! {
+    event Initialize(
+        bytes32 indexed id,
+        address indexed currency0,
+        address indexed currency1,
+        uint24 fee,
+        int24 tickSpacing,
+        address hooks,
+        uint160 sqrtPriceX96,
+        int24 tick
+    );
+    event Swap(
+        bytes32 indexed id,
+        address indexed sender,
+        int128 amount0,
+        int128 amount1,
+        uint160 sqrtPriceX96,
+        uint128 liquidity,
+        int24 tick,
+        uint24 fee
+    );
+
+    struct PoolKey {
+        address currency0;
+        address currency1;
+        uint24 fee;
+        int24 tickSpacing;
+        address hooks;
+    }
+
+    struct SwapParams {
+        bool zeroForOne;
+        int256 amountSpecified;
+        uint160 sqrtPriceLimitX96;
+    }
+
+    interface IPoolManager {
+        function unlock(bytes data) external returns (bytes);
+        function swap(PoolKey key, SwapParams params, bytes hookData) external returns (int256);
+    }
+}
+
+struct Hop {
+    log_idx: usize,
+    pool_id: FixedBytes<32>,
+    token_in: Address,
+    token_out: Address,
+    amount_in: u128,
+    amount_out: u128,
+}
+
+/// Splits the swaps of one `unlock`, in log order, into routes: a hop continues the current
+/// route when it spends the token the previous hop bought, and starts a new one otherwise.
+fn chain_hops(hops: Vec<Hop>) -> Vec<Vec<Hop>> {
+    let mut routes: Vec<Vec<Hop>> = Vec::new();
+    for hop in hops {
+        match routes.last_mut() {
+            Some(route) if route.last().map(|h| h.token_out) == Some(hop.token_in) => route.push(hop),
+            _ => routes.push(vec![hop]),
+        }
+    }
+    routes
+}
+
+/// Writes one row per swap route in a transaction. Swaps are grouped by the `unlock` call
+/// they ran under (found in the block traces; the whole transaction when traces are not
+/// available) and, within a group, chained in log order while each hop spends the token the
+/// previous hop bought. A route's input is what its first hop paid and its output what the
+/// last hop received.
+pub async fn process_uni_v4_routes(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let traces: HashMap<FixedBytes<32>, _> = components
+        .block_traces
+        .iter()
+        .flat_map(|traces| traces.iter())
+        .map(|trace| (trace.transaction_hash, trace))
+        .collect();
+
+    let mut swaps: Vec<(usize, TxHash, Address, String, usize, Swap)> = Vec::new();
+    let transactions = block.body().transactions.iter().zip(block.senders().iter());
+    for (tx_idx, ((tx, origin), receipt)) in transactions.zip(receipts.iter()).enumerate() {
+        let unlocks: Option<Vec<String>> = traces.get(tx.hash()).map(|trace| {
+            call_paths(trace, UNIV4_FACTORY_CONTRACT_ADDRESS, IPoolManager::swapCall::SELECTOR)
+                .into_iter()
+                .map(|path| {
+                    path.iter()
+                        .rev()
+                        .find(|f| f.to() == UNIV4_FACTORY_CONTRACT_ADDRESS && f.selector() == Some(IPoolManager::unlockCall::SELECTOR))
+                        .map(|f| f.trace_address())
+                        .unwrap_or_default()
+                })
+                .collect()
+        });
+
+        let mut swap_idx = 0;
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+
+            if log.topics().get(0) == Some(&Initialize::SIGNATURE_HASH) {
+                match Initialize::decode_raw_log(log.topics(), &log.data.data) {
+                    Ok(evt) => remember_pool_currencies(evt.id, evt.currency0, evt.currency1),
+                    Err(e) => { debug!("Failed to decode univ4 pool creation event: {:?}", e); }
+                }
+                continue;
+            }
+
+            if log.topics().get(0) != Some(&Swap::SIGNATURE_HASH) { continue; }
+
+            let unlock = unlocks.as_ref().and_then(|u| u.get(swap_idx).cloned()).unwrap_or_default();
+            swap_idx += 1;
+
+            match Swap::decode_raw_log(log.topics(), &log.data.data) {
+                Ok(evt) => swaps.push((tx_idx, *tx.hash(), *origin, unlock, log_idx, evt)),
+                Err(e) => { debug!("Failed to decode univ4 swap event: {:?}", e); }
+            }
+        }
+    }
+
+    if swaps.is_empty() { return Ok(()); }
+
+    let pool_ids: Vec<FixedBytes<32>> = swaps.iter().map(|s| s.5.id).collect();
+    let pools = pool_currencies(&components.client, &pool_ids).await?;
+
+    let mut groups: Vec<((usize, TxHash, Address, String), Vec<Hop>)> = Vec::new();
+    for (tx_idx, tx_hash, origin, unlock, log_idx, evt) in swaps {
+        let Some(&(currency0, currency1)) = pools.get(&evt.id) else {
+            debug!("Missing currencies for univ4 pool {}", evt.id);
+            continue;
+        };
+        let hop = if evt.amount0 < 0 {
+            Hop { log_idx, pool_id: evt.id, token_in: currency0, token_out: currency1, amount_in: evt.amount0.unsigned_abs(), amount_out: evt.amount1.unsigned_abs() }
+        } else {
+            Hop { log_idx, pool_id: evt.id, token_in: currency1, token_out: currency0, amount_in: evt.amount1.unsigned_abs(), amount_out: evt.amount0.unsigned_abs() }
+        };
+
+        let key = (tx_idx, tx_hash, origin, unlock);
+        match groups.last_mut() {
+            Some((last_key, hops)) if *last_key == key => hops.push(hop),
+            _ => groups.push((key, vec![hop])),
+        }
+    }
+
+    let mut route_counts: HashMap<usize, usize> = HashMap::new();
+    for ((tx_idx, tx_hash, origin, unlock), hops) in groups {
+        for route in chain_hops(hops) {
+            let route_idx = route_counts.entry(tx_idx).or_default();
+            let (first, last) = (&route[0], &route[route.len() - 1]);
+
+            let pool_path: Vec<String> = route.iter().map(|h| h.pool_id.to_string()).collect();
+            let mut token_path: Vec<String> = vec![first.token_in.to_checksum(Some(1))];
+            token_path.extend(route.iter().map(|h| h.token_out.to_checksum(Some(1))));
+
+            writer.write_record(values![
+                CHAIN_ID as i64,
+                block_number as i64,
+                block_timestamp,
+                tx_hash,
+                tx_idx as i64,
+                *route_idx as i64,
+                unlock.as_str(),
+                origin,
+                first.token_in,
+                last.token_out,
+                first.amount_in,
+                last.amount_out,
+                route.len() as i64,
+                pool_path.join(","),
+                token_path.join(","),
+                first.log_idx as i64,
+                last.log_idx as i64,
+            ]);
+            *route_idx += 1;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hop(log_idx: usize, token_in: Address, token_out: Address) -> Hop {
+        Hop { log_idx, pool_id: FixedBytes::ZERO, token_in, token_out, amount_in: 1, amount_out: 1 }
+    }
+
+    #[test]
+    fn hops_chain_while_each_spends_the_previous_output() {
+        let (eth, usdc, dai, wbtc) = (Address::ZERO, Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+        let routes = chain_hops(vec![
+            hop(0, eth, usdc),
+            hop(1, usdc, dai),
+            hop(2, wbtc, eth),
+            hop(3, usdc, eth),
+        ]);
+
+        let log_idxs: Vec<Vec<usize>> = routes.iter().map(|r| r.iter().map(|h| h.log_idx).collect()).collect();
+        assert_eq!(log_idxs, vec![vec![0, 1], vec![2], vec![3]]);
+        assert_eq!(routes[0][0].token_in, eth);
+        assert_eq!(routes[0][1].token_out, dai);
+    }
+}
@@ -0,0 +1,188 @@
+use crate::values;
+use crate::config::CONFIG;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use alloy::{sol, sol_types::SolEvent, primitives::{address, Address, FixedBytes, TxHash}};
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use std::collections::HashMap;
+use tracing::debug;
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const CHAIN_ID: u32 = 1;
+
+sol! {
+    event Swap(
+        bytes32 indexed id,
+        address indexed sender,
+        int128 amount0,
+        int128 amount1,
+        uint160 sqrtPriceX96,
+        uint128 liquidity,
+        int24 tick,
+        uint24 fee
+    );
+}
+
+struct PoolSwap {
+    tx_idx: usize,
+    tx_hash: TxHash,
+    log_idx: usize,
+    origin: Address,
+    sender: Address,
+    zero_for_one: bool,
+    amount0: i128,
+    amount1: i128,
+}
+
+impl PoolSwap {
+    /// Same transaction signer, or same swap `sender` unless it is a shared router or
+    /// aggregator calling the PoolManager for unrelated users.
+    fn same_actor(&self, other: &PoolSwap) -> bool {
+        let shared_sender = CONFIG.routers.contains(&self.sender) || CONFIG.aggregators.contains(&self.sender);
+        self.origin == other.origin || (self.sender == other.sender && !shared_sender)
+    }
+}
+
+/// Sandwiches among the swaps of one pool in log order, as the indexes of the front-run, the
+/// back-run and the victims.
+fn find_sandwiches(swaps: &[PoolSwap]) -> Vec<(usize, usize, Vec<usize>)> {
+    let mut sandwiches = Vec::new();
+    if swaps.len() < 3 { return sandwiches; }
+
+    let mut used = vec![false; swaps.len()];
+    for front_idx in 0..swaps.len() {
+        if used[front_idx] { continue; }
+        let front = &swaps[front_idx];
+
+        let back_idx = (front_idx + 1..swaps.len()).find(|&i| {
+            let back = &swaps[i];
+            !used[i] && back.tx_idx > front.tx_idx && back.zero_for_one != front.zero_for_one && back.same_actor(front)
+        });
+        let Some(back_idx) = back_idx else { continue; };
+        let back = &swaps[back_idx];
+
+        let victims: Vec<usize> = (front_idx + 1..back_idx)
+            .filter(|&i| {
+                let v = &swaps[i];
+                v.tx_idx > front.tx_idx && v.tx_idx < back.tx_idx && v.zero_for_one == front.zero_for_one && !v.same_actor(front)
+            })
+            .collect();
+        if victims.is_empty() { continue; }
+
+        used[front_idx] = true;
+        used[back_idx] = true;
+        sandwiches.push((front_idx, back_idx, victims));
+    }
+    sandwiches
+}
+
+/// Writes one row per sandwich found in a block: a front-run swap, one or more victim swaps
+/// in the same direction from other transactions, then a back-run swap in the opposite
+/// direction, all on the same pool. Front-run and back-run must share the transaction
+/// signer or a swap `sender` other than a known router or aggregator; victims must not be the
+/// same actor by that rule, so victims swapping through the router the front-run used are
+/// kept. The profit estimate is the attacker's net delta in each currency over the two swaps
+/// (positive is a gain), before gas and builder payments.
+pub async fn process_uni_v4_sandwiches(
+    block_data: &EthereumBlockData,
+    _components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let mut pools: HashMap<FixedBytes<32>, Vec<PoolSwap>> = HashMap::new();
+    let transactions = block.body().transactions.iter().zip(block.senders().iter());
+    for (tx_idx, ((tx, origin), receipt)) in transactions.zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+            if log.topics().get(0) != Some(&Swap::SIGNATURE_HASH) { continue; }
+
+            match Swap::decode_raw_log(log.topics(), &log.data.data) {
+                Ok(evt) => {
+                    pools.entry(evt.id).or_default().push(PoolSwap {
+                        tx_idx,
+                        tx_hash: *tx.hash(),
+                        log_idx,
+                        origin: *origin,
+                        sender: evt.sender,
+                        zero_for_one: evt.amount0 < 0,
+                        amount0: evt.amount0,
+                        amount1: evt.amount1,
+                    });
+                }
+                Err(e) => { debug!("Failed to decode univ4 swap event: {:?}", e); }
+            }
+        }
+    }
+
+    for (pool_id, swaps) in pools {
+        for (front_idx, back_idx, victims) in find_sandwiches(&swaps) {
+            let (front, back) = (&swaps[front_idx], &swaps[back_idx]);
+
+            let victim_hashes: Vec<String> = victims.iter().map(|&i| swaps[i].tx_hash.to_string()).collect();
+            let event_id = format!("{}#{}", front.tx_hash, front.log_idx);
+            writer.write_record(values![
+                CHAIN_ID as i64,
+                block_number as i64,
+                block_timestamp,
+                event_id,
+                pool_id,
+                front.origin,
+                front.sender,
+                front.zero_for_one,
+                front.tx_hash,
+                front.tx_idx as i64,
+                front.log_idx as i64,
+                back.tx_hash,
+                back.tx_idx as i64,
+                back.log_idx as i64,
+                victims.len() as i64,
+                victim_hashes.join(","),
+                front.amount0.saturating_add(back.amount0),
+                front.amount1.saturating_add(back.amount1),
+            ]);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNIVERSAL_ROUTER: Address = address!("0x66a9893cc07d91d95644aedd05d03f95e1dba8af");
+
+    fn swap(tx_idx: usize, origin: Address, sender: Address, zero_for_one: bool) -> PoolSwap {
+        let (amount0, amount1) = if zero_for_one { (-100, 90) } else { (95, -100) };
+        PoolSwap { tx_idx, tx_hash: TxHash::with_last_byte(tx_idx as u8), log_idx: 0, origin, sender, zero_for_one, amount0, amount1 }
+    }
+
+    #[test]
+    fn victims_through_the_front_run_router_are_kept() {
+        let (attacker, victim, bot) = (Address::repeat_byte(0xa1), Address::repeat_byte(0xb2), Address::repeat_byte(0xc3));
+        let swaps = vec![
+            swap(0, attacker, UNIVERSAL_ROUTER, true),
+            swap(1, victim, UNIVERSAL_ROUTER, true),
+            swap(2, attacker, bot, true),
+            swap(3, attacker, UNIVERSAL_ROUTER, false),
+        ];
+
+        assert_eq!(find_sandwiches(&swaps), vec![(0, 3, vec![1])]);
+    }
+
+    #[test]
+    fn router_sender_alone_does_not_pair_front_and_back_runs() {
+        let swaps = vec![
+            swap(0, Address::repeat_byte(1), UNIVERSAL_ROUTER, true),
+            swap(1, Address::repeat_byte(2), UNIVERSAL_ROUTER, true),
+            swap(2, Address::repeat_byte(3), UNIVERSAL_ROUTER, false),
+        ];
+
+        assert!(find_sandwiches(&swaps).is_empty());
+    }
+}
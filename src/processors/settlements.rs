@@ -0,0 +1,186 @@
+use crate::values;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use crate::traces::{calls, CallFrame};
+use crate::utils::currency_of;
+use alloy::{sol, sol_types::{SolCall, SolInterface}, primitives::{address, Address, Bytes, FixedBytes, I256, U256}};
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use std::collections::HashMap;
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const CHAIN_ID: u32 = 1;
+
+sol! {
+    interface IPoolManager {
+        function unlock(bytes data) external returns (bytes);
+        function sync(address currency) external;
+        function settle() external payable returns (uint256 paid);
+        function settleFor(address recipient) external payable returns (uint256 paid);
+        function take(address currency, address to, uint256 amount) external;
+        function mint(address to, uint256 id, uint256 amount) external;
+        function burn(address from, uint256 id, uint256 amount) external;
+        function clear(address currency, uint256 amount) external;
+    }
+}
+
+/// Writes one row per flash accounting call made to the PoolManager inside an `unlock`, as
+/// found in the block traces: `settle`/`settleFor`, `take`, `mint`, `burn` and `clear`.
+/// `delta` is the change to the credited account's currency delta (positive when the
+/// PoolManager owes it more), so summing `delta` by `unlock_trace_address` and `currency`
+/// gives the net settlement of each currency per unlock. `settle` pays the currency last
+/// passed to `sync` in the unlock, or the native currency when none is synced.
+pub async fn process_uni_v4_settlements(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let Some(block_traces) = components.block_traces.as_ref() else { return Ok(()); };
+
+    let block = &block_data.0;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let tx_indexes: HashMap<FixedBytes<32>, usize> = block
+        .body()
+        .transactions
+        .iter()
+        .enumerate()
+        .map(|(tx_idx, tx)| (*tx.hash(), tx_idx))
+        .collect();
+
+    for trace in block_traces.iter() {
+        let Some(&tx_idx) = tx_indexes.get(&trace.transaction_hash) else { continue; };
+
+        let frames: Vec<CallFrame<'_>> = calls(trace).collect();
+        let mut synced: HashMap<String, Address> = HashMap::new();
+
+        for (call_idx, call) in frames.iter().enumerate() {
+            if call.to() != UNIV4_FACTORY_CONTRACT_ADDRESS || !call.success() { continue; }
+            let Ok(decoded) = IPoolManager::IPoolManagerCalls::abi_decode(call.input()) else { continue; };
+
+            let Some(unlock) = frames[..call_idx].iter().rev().find(|f| {
+                f.is_ancestor_of(call)
+                    && f.to() == UNIV4_FACTORY_CONTRACT_ADDRESS
+                    && f.selector() == Some(IPoolManager::unlockCall::SELECTOR)
+            }) else { continue; };
+            let unlock_trace_address = unlock.trace_address();
+
+            let Some((action, currency, account, amount, delta)) =
+                flash_accounting(decoded, call.from(), call.output(), &mut synced, &unlock_trace_address) else { continue; };
+
+            let call_id = format!("{}#{}", trace.transaction_hash, call_idx);
+            writer.write_record(values![
+                CHAIN_ID as i64,
+                block_number as i64,
+                block_timestamp,
+                trace.transaction_hash,
+                tx_idx as i64,
+                call.trace_address(),
+                call_id,
+                unlock_trace_address,
+                unlock.from(),
+                call.from(),
+                action,
+                currency,
+                account,
+                amount,
+                delta,
+            ]);
+        }
+    }
+
+    Ok(())
+}
+
+/// The action, currency, credited account, amount and currency delta of a flash
+/// accounting call made in the unlock at `unlock_trace_address`. `sync` only records the
+/// currency the next `settle` of the unlock pays, so it and `unlock` give `None`.
+fn flash_accounting(
+    decoded: IPoolManager::IPoolManagerCalls,
+    caller: Address,
+    output: Option<&Bytes>,
+    synced: &mut HashMap<String, Address>,
+    unlock_trace_address: &str,
+) -> Option<(&'static str, Address, Address, U256, I256)> {
+    let (action, currency, account, amount, credit) = match decoded {
+        IPoolManager::IPoolManagerCalls::sync(c) => {
+            synced.insert(unlock_trace_address.to_string(), c.currency);
+            return None;
+        }
+        IPoolManager::IPoolManagerCalls::settle(_) => {
+            let currency = synced.remove(unlock_trace_address).unwrap_or(Address::ZERO);
+            ("settle", currency, caller, settled_amount(output), true)
+        }
+        IPoolManager::IPoolManagerCalls::settleFor(c) => {
+            let currency = synced.remove(unlock_trace_address).unwrap_or(Address::ZERO);
+            ("settle", currency, c.recipient, settled_amount(output), true)
+        }
+        IPoolManager::IPoolManagerCalls::take(c) => ("take", c.currency, c.to, c.amount, false),
+        IPoolManager::IPoolManagerCalls::mint(c) => ("mint", currency_of(c.id), c.to, c.amount, false),
+        IPoolManager::IPoolManagerCalls::burn(c) => ("burn", currency_of(c.id), c.from, c.amount, true),
+        IPoolManager::IPoolManagerCalls::clear(c) => ("clear", c.currency, caller, c.amount, false),
+        IPoolManager::IPoolManagerCalls::unlock(_) => return None,
+    };
+
+    let magnitude = I256::try_from(amount).unwrap_or(I256::MAX);
+    let delta = if credit { magnitude } else { -magnitude };
+    Some((action, currency, account, amount, delta))
+}
+
+fn settled_amount(output: Option<&Bytes>) -> U256 {
+    output
+        .and_then(|output| IPoolManager::settleCall::abi_decode_returns(output).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use IPoolManager::IPoolManagerCalls as Calls;
+
+    const USDC: Address = address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+    const WETH: Address = address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+
+    #[test]
+    fn flash_accounting_calls_credit_and_debit_currency_deltas() {
+        let router = Address::repeat_byte(1);
+        let user = Address::repeat_byte(2);
+        let paid = Bytes::copy_from_slice(&U256::from(1_000).to_be_bytes::<32>());
+        let mut synced = HashMap::new();
+        let mut apply = |call: Calls, output: Option<&Bytes>, unlock: &str| {
+            flash_accounting(call, router, output, &mut synced, unlock)
+        };
+
+        assert_eq!(apply(Calls::sync(IPoolManager::syncCall { currency: USDC }), None, "0"), None);
+        // A sync in another unlock doesn't change which currency this one settles
+        assert_eq!(apply(Calls::sync(IPoolManager::syncCall { currency: WETH }), None, "1"), None);
+        assert_eq!(
+            apply(Calls::settle(IPoolManager::settleCall {}), Some(&paid), "0"),
+            Some(("settle", USDC, router, U256::from(1_000), I256::try_from(1_000).unwrap()))
+        );
+        // Without a sync, settle pays the native currency
+        assert_eq!(
+            apply(Calls::settleFor(IPoolManager::settleForCall { recipient: user }), Some(&paid), "0"),
+            Some(("settle", Address::ZERO, user, U256::from(1_000), I256::try_from(1_000).unwrap()))
+        );
+        assert_eq!(
+            apply(Calls::take(IPoolManager::takeCall { currency: WETH, to: user, amount: U256::from(5) }), None, "0"),
+            Some(("take", WETH, user, U256::from(5), I256::try_from(-5).unwrap()))
+        );
+
+        let usdc_id = U256::from_be_slice(USDC.as_slice());
+        assert_eq!(
+            apply(Calls::mint(IPoolManager::mintCall { to: user, id: usdc_id, amount: U256::from(7) }), None, "1"),
+            Some(("mint", USDC, user, U256::from(7), I256::try_from(-7).unwrap()))
+        );
+        assert_eq!(
+            apply(Calls::burn(IPoolManager::burnCall { from: user, id: usdc_id, amount: U256::from(7) }), None, "1"),
+            Some(("burn", USDC, user, U256::from(7), I256::try_from(7).unwrap()))
+        );
+        assert_eq!(
+            apply(Calls::settle(IPoolManager::settleCall {}), None, "1"),
+            Some(("settle", WETH, router, U256::ZERO, I256::ZERO))
+        );
+    }
+}
@@ -1,11 +1,13 @@
 use crate::values;
 use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::config::CONFIG;
 use crate::storage::writer::ClickhouseWriter as DbWriter;
-use alloy::{sol, sol_types::SolEvent, primitives::{address, Address}};
-use reth_node_api::FullNodeComponents;
+use crate::traces::call_paths;
+use alloy::{sol, sol_types::{SolCall, SolEvent}, primitives::{address, Address, FixedBytes}};
+use alloy_consensus::Transaction;
 use eyre::Result;
 use chrono::{Utc, TimeZone};
-use reth_rpc_eth_api::helpers::FullEthApi;
+use std::collections::HashMap;
 use tracing::debug;
 
 const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
@@ -22,11 +24,35 @@ sol! {
         int24 tick,
         uint24 fee
     );
+
+    struct PoolKey {
+        address currency0;
+        address currency1;
+        uint24 fee;
+        int24 tickSpacing;
+        address hooks;
+    }
+
+    struct SwapParams {
+        bool zeroForOne;
+        int256 amountSpecified;
+        uint160 sqrtPriceLimitX96;
+    }
+
+    interface IPoolManager {
+        function swap(PoolKey key, SwapParams params, bytes hookData) external returns (int256);
+    }
 }
 
-pub async fn process_uni_v4_swaps<Node: FullNodeComponents, EthApi: FullEthApi>(
+/// Writes one row per `Swap` event. Besides the PoolManager caller (`sender`), each swap is
+/// attributed to the transaction signer (`origin`), the innermost configured router and
+/// the outermost configured aggregator on its call path. The call path comes from the
+/// block traces, matching the n-th successful `PoolManager.swap` call of a transaction to
+/// its n-th `Swap` event; without traces only the transaction's `to` is classified.
+/// Unattributed columns hold the zero address.
+pub async fn process_uni_v4_swaps(
     block_data: &EthereumBlockData,
-    _components: ProcessingComponents<Node, EthApi>,
+    components: ProcessingComponents,
     writer: &mut DbWriter,
 ) -> Result<()> {
     let block = &block_data.0;
@@ -34,11 +60,36 @@ pub async fn process_uni_v4_swaps<Node: FullNodeComponents, EthApi: FullEthApi>(
     let block_number = block.num_hash().number;
     let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
 
-    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+    let traces: HashMap<FixedBytes<32>, _> = components
+        .block_traces
+        .iter()
+        .flat_map(|traces| traces.iter())
+        .map(|trace| (trace.transaction_hash, trace))
+        .collect();
+
+    let transactions = block.body().transactions.iter().zip(block.senders().iter());
+    for (tx_idx, ((tx, origin), receipt)) in transactions.zip(receipts.iter()).enumerate() {
+        let swap_paths: Option<Vec<Vec<Address>>> = traces.get(tx.hash()).map(|trace| {
+            call_paths(trace, UNIV4_FACTORY_CONTRACT_ADDRESS, IPoolManager::swapCall::SELECTOR)
+                .into_iter()
+                .map(|path| path.iter().map(|f| f.to()).collect())
+                .collect()
+        });
+
+        let tx_to = tx.to();
+        let mut swap_idx = 0;
         for (log_idx, log) in receipt.logs.iter().enumerate() {
             if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
             if log.topics().get(0) != Some(&Swap::SIGNATURE_HASH) { continue; }
 
+            let call_path = match &swap_paths {
+                Some(paths) => paths.get(swap_idx).map(Vec::as_slice).unwrap_or_default(),
+                None => tx_to.as_slice(),
+            };
+            swap_idx += 1;
+            let router = call_path.iter().rev().find(|a| CONFIG.routers.contains(a)).copied().unwrap_or_default();
+            let aggregator = call_path.iter().find(|a| CONFIG.aggregators.contains(a)).copied().unwrap_or_default();
+
             match Swap::decode_raw_log(log.topics(), &log.data.data) {
                 Ok(evt) => {
                     let event_id = format!("{}#{}", tx.hash(), log_idx);
@@ -59,6 +110,9 @@ pub async fn process_uni_v4_swaps<Node: FullNodeComponents, EthApi: FullEthApi>(
                         evt.liquidity,
                         evt.tick,
                         evt.fee,
+                        origin,
+                        router,
+                        aggregator,
                     ]);
                 }
                 Err(e) => { debug!("Failed to decode univ4 swap event: {:?}", e); }
@@ -0,0 +1,220 @@
+use crate::values;
+use crate::config::CONFIG;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::processors::tokens::resolve_tokens;
+use crate::storage::reader::{pool_currencies, remember_pool_currencies};
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use crate::utils::sqrt_price_x96_to_price;
+use alloy::{sol, sol_types::SolEvent, primitives::{address, Address, FixedBytes}};
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::debug;
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const CHAIN_ID: u32 = 1;
+
+sol! {
+    event Initialize(
+        bytes32 indexed id,
+        address indexed currency0,
+        address indexed currency1,
+        uint24 fee,
+        int24 tickSpacing,
+        address hooks,
+        uint160 sqrtPriceX96,
+        int24 tick
+    );
+    event Swap(
+        bytes32 indexed id,
+        address indexed sender,
+        int128 amount0,
+        int128 amount1,
+        uint160 sqrtPriceX96,
+        uint128 liquidity,
+        int24 tick,
+        uint24 fee
+    );
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct ReferenceSwap {
+    pool_id: String,
+    sqrt_price_x96: String,
+}
+
+/// Writes one row per `Swap` with decimal-adjusted amounts, the post-swap price of
+/// currency0 in currency1, and the swap volume in USD. Stablecoins are valued at one
+/// dollar; ETH and WETH at the latest price of the configured reference pools. The USD
+/// columns are NULL when no price is known: no reference pool for ETH, or neither side a
+/// stablecoin nor ETH.
+pub async fn process_uni_v4_swaps_enriched(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let mut events = Vec::new();
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+
+            if log.topics().get(0) == Some(&Initialize::SIGNATURE_HASH) {
+                match Initialize::decode_raw_log(log.topics(), &log.data.data) {
+                    Ok(evt) => remember_pool_currencies(evt.id, evt.currency0, evt.currency1),
+                    Err(e) => { debug!("Failed to decode univ4 pool creation event: {:?}", e); }
+                }
+                continue;
+            }
+
+            if log.topics().get(0) != Some(&Swap::SIGNATURE_HASH) { continue; }
+
+            match Swap::decode_raw_log(log.topics(), &log.data.data) {
+                Ok(evt) => events.push((tx_idx, tx.hash(), log_idx, evt)),
+                Err(e) => { debug!("Failed to decode univ4 swap event: {:?}", e); }
+            }
+        }
+    }
+
+    if events.is_empty() { return Ok(()); }
+
+    let mut pool_ids: Vec<FixedBytes<32>> = events.iter().map(|e| e.3.id).collect();
+    pool_ids.extend(CONFIG.usd_reference_pools.iter().copied());
+    let pools = pool_currencies(&components.client, &pool_ids).await?;
+
+    let tokens: Vec<Address> = pools.values().flat_map(|(c0, c1)| [*c0, *c1]).collect();
+    let decimals: HashMap<Address, u32> = resolve_tokens(components.eth_api.as_deref(), &components.client, block_number, &tokens)
+        .await?
+        .into_iter()
+        .map(|(token, metadata)| (token, metadata.decimals))
+        .collect();
+
+    let pool_price = |pool_id: &FixedBytes<32>, sqrt_price_x96: f64| -> Option<(Address, Address, f64)> {
+        let (currency0, currency1) = pools.get(pool_id)?;
+        let price = sqrt_price_x96_to_price(sqrt_price_x96, *decimals.get(currency0)?, *decimals.get(currency1)?);
+        Some((*currency0, *currency1, price))
+    };
+
+    let mut eth_usd = load_reference_swap(&components.client, block_number)
+        .await?
+        .and_then(|(pool_id, sqrt_price_x96)| pool_price(&pool_id, sqrt_price_x96))
+        .and_then(|(currency0, currency1, price)| eth_usd_from(currency0, currency1, price));
+
+    for (tx_idx, tx_hash, log_idx, evt) in events {
+        let Some((currency0, currency1, price)) = pool_price(&evt.id, f64::from(evt.sqrtPriceX96)) else {
+            debug!("Missing currencies or decimals for univ4 pool {}", evt.id);
+            continue;
+        };
+
+        if CONFIG.usd_reference_pools.contains(&evt.id) {
+            if let Some(price) = eth_usd_from(currency0, currency1, price) { eth_usd = Some(price); }
+        }
+
+        let amount0 = evt.amount0 as f64 / 10f64.powi(decimals[&currency0] as i32);
+        let amount1 = evt.amount1 as f64 / 10f64.powi(decimals[&currency1] as i32);
+        let volume_usd = volume_usd(currency0, currency1, amount0, amount1, eth_usd);
+
+        let event_id = format!("{}#{}", tx_hash, log_idx);
+        writer.write_record(values![
+            CHAIN_ID as i64,
+            block_number as i64,
+            block_timestamp,
+            tx_hash,
+            tx_idx as i64,
+            log_idx as i64,
+            event_id,
+            evt.id,
+            currency0,
+            currency1,
+            amount0,
+            amount1,
+            price,
+            eth_usd,
+            volume_usd,
+        ]);
+    }
+
+    Ok(())
+}
+
+/// ETH/USD price from the price of currency0 in currency1 of an ETH/stablecoin pool.
+fn eth_usd_from(currency0: Address, currency1: Address, price: f64) -> Option<f64> {
+    if CONFIG.eth_currencies.contains(&currency0) && CONFIG.stablecoins.contains(&currency1) { return Some(price); }
+    if CONFIG.eth_currencies.contains(&currency1) && CONFIG.stablecoins.contains(&currency0) && price > 0.0 { return Some(1.0 / price); }
+    None
+}
+
+/// USD value of a swap's decimal-adjusted amounts, from its stablecoin side if any, else
+/// from its ETH side at `eth_usd`.
+fn volume_usd(currency0: Address, currency1: Address, amount0: f64, amount1: f64, eth_usd: Option<f64>) -> Option<f64> {
+    if CONFIG.stablecoins.contains(&currency0) {
+        Some(amount0.abs())
+    } else if CONFIG.stablecoins.contains(&currency1) {
+        Some(amount1.abs())
+    } else if CONFIG.eth_currencies.contains(&currency0) {
+        eth_usd.map(|eth_usd| amount0.abs() * eth_usd)
+    } else if CONFIG.eth_currencies.contains(&currency1) {
+        eth_usd.map(|eth_usd| amount1.abs() * eth_usd)
+    } else {
+        None
+    }
+}
+
+/// Latest swap of a reference pool before `block_number`, as `(pool_id, sqrtPriceX96)`.
+async fn load_reference_swap(client: &clickhouse::Client, block_number: u64) -> Result<Option<(FixedBytes<32>, f64)>> {
+    if CONFIG.usd_reference_pools.is_empty() { return Ok(None); }
+
+    let pool_ids: Vec<String> = CONFIG.usd_reference_pools.iter().map(|id| id.to_string()).collect();
+    let row = client
+        .query(
+            "SELECT toString(pool_id) AS pool_id, toString(sqrt_price_x96) AS sqrt_price_x96 \
+             FROM uni_v4_swaps \
+             WHERE has(?, toString(pool_id)) AND block_number < ? \
+             ORDER BY block_number DESC, transaction_index DESC, log_index DESC \
+             LIMIT 1",
+        )
+        .bind(pool_ids)
+        .bind(block_number)
+        .fetch_optional::<ReferenceSwap>()
+        .await?;
+
+    Ok(row.and_then(|row| Some((row.pool_id.parse().ok()?, row.sqrt_price_x96.parse().ok()?))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDC: Address = address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+    const DAI: Address = address!("0x6B175474E89094C44Da98b954EedeAC495271d0F");
+    const WETH: Address = address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+    const UNI: Address = address!("0x1f9840a85d5aF5bf1D1762F925BDADdC4201F984");
+
+    #[test]
+    fn eth_usd_is_read_from_either_side_of_a_reference_pool() {
+        // 2000 USDC per ETH: sqrt(2000 * 10^6 / 10^18) * 2^96
+        let sqrt_price_x96 = (2000e-12f64).sqrt() * 2f64.powi(96);
+        let price = sqrt_price_x96_to_price(sqrt_price_x96, 18, 6);
+        assert!((price - 2000.0).abs() < 1e-6);
+
+        assert_eq!(eth_usd_from(Address::ZERO, USDC, price), Some(price));
+        assert_eq!(eth_usd_from(USDC, WETH, 0.0005), Some(2000.0));
+        assert_eq!(eth_usd_from(USDC, WETH, 0.0), None);
+        assert_eq!(eth_usd_from(WETH, UNI, 500.0), None);
+    }
+
+    #[test]
+    fn volume_prefers_the_stablecoin_side() {
+        assert_eq!(volume_usd(USDC, WETH, -3000.0, 1.5, Some(2100.0)), Some(3000.0));
+        assert_eq!(volume_usd(WETH, DAI, 1.5, -2990.0, Some(2100.0)), Some(2990.0));
+        assert_eq!(volume_usd(Address::ZERO, UNI, -1.5, 400.0, Some(2000.0)), Some(3000.0));
+        assert_eq!(volume_usd(UNI, WETH, 400.0, -1.5, Some(2000.0)), Some(3000.0));
+        assert_eq!(volume_usd(UNI, WETH, 400.0, -1.5, None), None);
+        assert_eq!(volume_usd(UNI, Address::repeat_byte(1), 400.0, -1.5, Some(2000.0)), None);
+    }
+}
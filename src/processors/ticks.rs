@@ -0,0 +1,166 @@
+use crate::values;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::reader::latest_rows_before;
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use alloy::{sol, sol_types::SolEvent, primitives::{address, Address, FixedBytes}};
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::debug;
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const CHAIN_ID: u32 = 1;
+
+sol! {
+    event ModifyLiquidity(
+        bytes32 indexed id,
+        address indexed sender,
+        int24 tickLower,
+        int24 tickUpper,
+        int256 liquidityDelta,
+        bytes32 salt
+    );
+}
+
+/// Latest known `liquidityGross`/`liquidityNet` of a tick, as stored in `uni_v4_ticks`.
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct TickState {
+    pool_id: String,
+    tick: i32,
+    liquidity_gross: String,
+    liquidity_net: String,
+}
+
+/// Writes one row per tick touched by a `ModifyLiquidity` event, carrying the tick's
+/// `liquidityGross` and `liquidityNet` after the change. The latest row per
+/// `(pool_id, tick)` matches `StateView.getTickInfo`; reverting a block deletes its rows
+/// so the previous snapshot becomes the latest again.
+pub async fn process_uni_v4_ticks(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let mut events = Vec::new();
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+            if log.topics().get(0) != Some(&ModifyLiquidity::SIGNATURE_HASH) { continue; }
+
+            match ModifyLiquidity::decode_raw_log(log.topics(), &log.data.data) {
+                Ok(evt) => events.push((tx_idx, tx.hash(), log_idx, log.address, evt)),
+                Err(e) => { debug!("Failed to decode univ4 modify liquidity event: {:?}", e); }
+            }
+        }
+    }
+
+    if events.is_empty() { return Ok(()); }
+
+    let mut state = load_tick_state(&components.client, block_number, &events).await?;
+
+    for (tx_idx, tx_hash, log_idx, log_address, evt) in events {
+        let liquidity_delta: i128 = match evt.liquidityDelta.try_into() {
+            Ok(delta) => delta,
+            Err(e) => { debug!("Liquidity delta out of range for {}#{}: {:?}", tx_hash, log_idx, e); continue; }
+        };
+        if liquidity_delta == 0 { continue; }
+
+        let event_id = format!("{}#{}", tx_hash, log_idx);
+        let ticks = update_ticks(&mut state, evt.id, evt.tickLower.as_i32(), evt.tickUpper.as_i32(), liquidity_delta);
+
+        for (tick, net_delta, gross, net) in ticks {
+            writer.write_record(values![
+                CHAIN_ID as i64,
+                block_number as i64,
+                block_timestamp,
+                tx_hash,
+                tx_idx as i64,
+                log_idx as i64,
+                log_address,
+                event_id.as_str(),
+                evt.id,
+                tick,
+                gross,
+                net,
+                net_delta,
+            ]);
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies a liquidity change to both ticks of a range as `Pool.modifyLiquidity` does: both
+/// gain `liquidity_delta` of gross liquidity, and the net liquidity crossing them upwards
+/// grows by it at the lower tick and shrinks by it at the upper tick. Returns each tick with
+/// its net delta and its liquidity after the change.
+fn update_ticks(
+    state: &mut HashMap<(FixedBytes<32>, i32), (u128, i128)>,
+    pool_id: FixedBytes<32>,
+    tick_lower: i32,
+    tick_upper: i32,
+    liquidity_delta: i128,
+) -> [(i32, i128, u128, i128); 2] {
+    [(tick_lower, liquidity_delta), (tick_upper, -liquidity_delta)].map(|(tick, net_delta)| {
+        let (gross, net) = state.entry((pool_id, tick)).or_insert((0, 0));
+        *gross = gross.saturating_add_signed(liquidity_delta);
+        *net = net.saturating_add(net_delta);
+        (tick, net_delta, *gross, *net)
+    })
+}
+
+async fn load_tick_state(
+    client: &clickhouse::Client,
+    block_number: u64,
+    events: &[(usize, &FixedBytes<32>, usize, Address, ModifyLiquidity)],
+) -> Result<HashMap<(FixedBytes<32>, i32), (u128, i128)>> {
+    let pool_ids: Vec<String> = events.iter().map(|e| e.4.id.to_string()).collect();
+    let ticks: Vec<String> = events
+        .iter()
+        .flat_map(|e| [e.4.tickLower.as_i32(), e.4.tickUpper.as_i32()])
+        .map(|tick| tick.to_string())
+        .collect();
+
+    let rows: Vec<TickState> = latest_rows_before(
+        client,
+        "uni_v4_ticks",
+        "toString(pool_id) AS pool_id, tick, toString(liquidity_gross) AS liquidity_gross, \
+         toString(liquidity_net) AS liquidity_net",
+        &[("pool_id", pool_ids), ("tick", ticks)],
+        block_number,
+    )
+    .await?;
+
+    let mut state = HashMap::with_capacity(rows.len());
+    for row in rows {
+        let Ok(pool_id) = row.pool_id.parse::<FixedBytes<32>>() else { continue; };
+        let gross = row.liquidity_gross.parse::<u128>().unwrap_or_default();
+        let net = row.liquidity_net.parse::<i128>().unwrap_or_default();
+        state.insert((pool_id, row.tick), (gross, net));
+    }
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_track_gross_and_net_liquidity() {
+        let pool_id = FixedBytes::repeat_byte(1);
+        let mut state = HashMap::from([((pool_id, 120), (10, -10))]);
+
+        assert_eq!(update_ticks(&mut state, pool_id, -60, 60, 100), [(-60, 100, 100, 100), (60, -100, 100, -100)]);
+        assert_eq!(update_ticks(&mut state, pool_id, 60, 120, 50), [(60, 50, 150, -50), (120, -50, 60, -60)]);
+        assert_eq!(update_ticks(&mut state, pool_id, -60, 60, -100), [(-60, -100, 0, 0), (60, 100, 50, 50)]);
+
+        assert_eq!(state[&(pool_id, 60)], (50, 50));
+        assert_eq!(state[&(pool_id, 120)], (60, -60));
+        assert!(!state.contains_key(&(FixedBytes::repeat_byte(2), 60)));
+    }
+}
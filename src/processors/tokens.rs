@@ -0,0 +1,347 @@
+use crate::values;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use alloy::{sol, sol_types::{SolCall, SolEvent}, primitives::{address, Address, Bytes, FixedBytes, TxHash}};
+use alloy_network::{Network, TransactionBuilder};
+use alloy_rpc_types::BlockId;
+use alloy_rpc_types_eth::state::EvmOverrides;
+use futures::future::BoxFuture;
+use reth_rpc_convert::RpcTypes;
+use reth_rpc_eth_api::{helpers::{EthCall, FullEthApi}, EthApiTypes};
+use eyre::Result;
+use chrono::{DateTime, Utc, TimeZone};
+use serde::Deserialize;
+use std::{collections::{HashMap, HashSet}, sync::Mutex};
+use tracing::{debug, warn};
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const CHAIN_ID: u32 = 1;
+/// Blocks after its `Initialize` during which a currency whose metadata calls fail is retried,
+/// after which it is written with NULL metadata.
+const TOKEN_RETRY_BLOCKS: u64 = 100;
+
+sol! {
+    event Initialize(
+        bytes32 indexed id,
+        address indexed currency0,
+        address indexed currency1,
+        uint24 fee,
+        int24 tickSpacing,
+        address hooks,
+        uint160 sqrtPriceX96,
+        int24 tick
+    );
+
+    interface IERC20Metadata {
+        function name() external view returns (string);
+        function symbol() external view returns (string);
+        function decimals() external view returns (uint8);
+    }
+}
+
+#[derive(Debug, Clone, clickhouse::Row, Deserialize)]
+pub struct TokenMetadata {
+    pub address: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u32,
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct TokenAddress {
+    address: String,
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct PendingTokenRow {
+    block_number: u64,
+    block_timestamp: i64,
+    transaction_hash: String,
+    transaction_index: u32,
+    log_index: u32,
+    pool_id: String,
+    currency: String,
+}
+
+/// A currency seen in an `Initialize` event whose metadata could not be fetched yet.
+#[derive(Debug, Clone)]
+struct PendingToken {
+    block_number: u64,
+    block_timestamp: DateTime<Utc>,
+    tx_hash: TxHash,
+    tx_idx: usize,
+    log_idx: usize,
+    pool_id: FixedBytes<32>,
+    currency: Address,
+}
+
+lazy_static::lazy_static! {
+    static ref TOKEN_CACHE: Mutex<HashMap<Address, TokenMetadata>> = Mutex::new(HashMap::new());
+    static ref PENDING_TOKENS: Mutex<Option<Vec<PendingToken>>> = Mutex::new(None);
+}
+
+/// Writes one row per currency seen for the first time in an `Initialize` event, with its
+/// `name()`, `symbol()` and `decimals()` read through `eth_call` at that block. Tokens
+/// returning `bytes32` instead of `string` are decoded too, and the native currency
+/// (`address(0)`) is written as ETH. Currencies whose calls fail are retried with the
+/// following `TOKEN_RETRY_BLOCKS` blocks and written for the block that initialized them,
+/// with NULL metadata when they never resolve. After a restart, the currencies still being
+/// retried are found again from `uni_v4_pools`.
+pub async fn process_tokens(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let pending = PENDING_TOKENS.lock().unwrap().take();
+    let mut events: Vec<PendingToken> = match pending {
+        Some(pending) => pending,
+        None => load_pending_tokens(&components.client, block_number).await?,
+    };
+    events.retain(|token| token.block_number < block_number);
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV4_FACTORY_CONTRACT_ADDRESS { continue; }
+            if log.topics().get(0) != Some(&Initialize::SIGNATURE_HASH) { continue; }
+
+            match Initialize::decode_raw_log(log.topics(), &log.data.data) {
+                Ok(evt) => {
+                    for currency in [evt.currency0, evt.currency1] {
+                        events.push(PendingToken {
+                            block_number,
+                            block_timestamp,
+                            tx_hash: *tx.hash(),
+                            tx_idx,
+                            log_idx,
+                            pool_id: evt.id,
+                            currency,
+                        });
+                    }
+                }
+                Err(e) => { debug!("Failed to decode univ4 pool creation event: {:?}", e); }
+            }
+        }
+    }
+
+    if events.is_empty() {
+        *PENDING_TOKENS.lock().unwrap() = Some(Vec::new());
+        return Ok(());
+    }
+
+    let currencies: Vec<Address> = events.iter().map(|e| e.currency).collect();
+    let mut written = written_tokens(&components.client, block_number, &currencies).await?;
+    let metadata = resolve_tokens(components.eth_api.as_deref(), &components.client, block_number, &currencies).await?;
+
+    let mut pending = Vec::new();
+    for event in events {
+        if written.contains(&event.currency) { continue; }
+        let token = metadata.get(&event.currency);
+        if token.is_none() && event.block_number + TOKEN_RETRY_BLOCKS > block_number {
+            if !pending.iter().any(|p: &PendingToken| p.currency == event.currency) { pending.push(event); }
+            continue;
+        }
+        if token.is_none() {
+            warn!("Writing token {} without metadata after {} blocks of failed calls", event.currency, TOKEN_RETRY_BLOCKS);
+        }
+        written.insert(event.currency);
+
+        writer.write_record(values![
+            CHAIN_ID as i64,
+            event.block_number as i64,
+            event.block_timestamp,
+            event.tx_hash,
+            event.tx_idx as i64,
+            event.log_idx as i64,
+            event.pool_id,
+            event.currency,
+            token.map(|t| t.name.as_str()),
+            token.map(|t| t.symbol.as_str()),
+            token.map(|t| t.decimals as i64),
+        ]);
+    }
+    *PENDING_TOKENS.lock().unwrap() = Some(pending);
+
+    Ok(())
+}
+
+/// Resolves token metadata from the in-process cache, then the `tokens` table, and
+/// finally through `eth_call` at `block_number` when `eth_api` is available. Tokens that
+/// cannot be resolved, including those whose calls fail, are left out and not cached, so
+/// they are tried again the next time they are resolved.
+pub async fn resolve_tokens(
+    eth_api: Option<&dyn ContractCaller>,
+    client: &clickhouse::Client,
+    block_number: u64,
+    tokens: &[Address],
+) -> Result<HashMap<Address, TokenMetadata>> {
+    let mut resolved = HashMap::with_capacity(tokens.len());
+    let mut missing = Vec::new();
+    {
+        let cache = TOKEN_CACHE.lock().unwrap();
+        for token in tokens {
+            match cache.get(token) {
+                Some(metadata) => { resolved.insert(*token, metadata.clone()); }
+                None => missing.push(*token),
+            }
+        }
+    }
+    missing.sort_unstable();
+    missing.dedup();
+    if missing.is_empty() { return Ok(resolved); }
+
+    let addresses: Vec<String> = missing.iter().map(|token| token.to_checksum(Some(1))).collect();
+    let rows = client
+        .query(
+            "SELECT address, assumeNotNull(name) AS name, assumeNotNull(symbol) AS symbol, \
+             assumeNotNull(decimals) AS decimals \
+             FROM tokens WHERE has(?, address) AND decimals IS NOT NULL LIMIT 1 BY address",
+        )
+        .bind(addresses)
+        .fetch_all::<TokenMetadata>()
+        .await?;
+    for row in rows {
+        if let Ok(token) = row.address.parse::<Address>() {
+            resolved.insert(token, row);
+        }
+    }
+
+    let block_id = BlockId::from(block_number);
+    for token in missing {
+        if resolved.contains_key(&token) { continue; }
+
+        let metadata = if token == Address::ZERO {
+            TokenMetadata {
+                address: token.to_checksum(Some(1)),
+                name: "Ether".to_string(),
+                symbol: "ETH".to_string(),
+                decimals: 18,
+            }
+        } else if let Some(eth_api) = eth_api {
+            let name = eth_api.call_contract(token, IERC20Metadata::nameCall {}.abi_encode(), block_id).await;
+            let symbol = eth_api.call_contract(token, IERC20Metadata::symbolCall {}.abi_encode(), block_id).await;
+            let decimals = eth_api
+                .call_contract(token, IERC20Metadata::decimalsCall {}.abi_encode(), block_id)
+                .await
+                .and_then(|out| IERC20Metadata::decimalsCall::abi_decode_returns(&out).ok());
+
+            let (Some(name), Some(symbol), Some(decimals)) = (name, symbol, decimals) else {
+                debug!("Failed to fetch metadata of token {} at block {}", token, block_number);
+                continue;
+            };
+            TokenMetadata {
+                address: token.to_checksum(Some(1)),
+                name: decode_string(&name),
+                symbol: decode_string(&symbol),
+                decimals: u32::from(decimals),
+            }
+        } else {
+            continue;
+        };
+        resolved.insert(token, metadata);
+    }
+
+    let mut cache = TOKEN_CACHE.lock().unwrap();
+    for (token, metadata) in &resolved {
+        cache.entry(*token).or_insert_with(|| metadata.clone());
+    }
+    Ok(resolved)
+}
+
+/// Currencies initialized in the `TOKEN_RETRY_BLOCKS` blocks before `block_number` that have
+/// no `tokens` row yet, i.e. those that were still being retried when the process stopped.
+async fn load_pending_tokens(client: &clickhouse::Client, block_number: u64) -> Result<Vec<PendingToken>> {
+    let rows = client
+        .query(
+            "SELECT block_number, toInt64(toUnixTimestamp(block_timestamp)) AS block_timestamp, transaction_hash, \
+             transaction_index, log_index, toString(pool_id) AS pool_id, currency \
+             FROM uni_v4_pools ARRAY JOIN [currency0, currency1] AS currency \
+             WHERE block_number >= ? AND block_number < ? AND currency NOT IN (SELECT address FROM tokens) \
+             ORDER BY block_number, transaction_index, log_index",
+        )
+        .bind(block_number.saturating_sub(TOKEN_RETRY_BLOCKS))
+        .bind(block_number)
+        .fetch_all::<PendingTokenRow>()
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            Some(PendingToken {
+                block_number: row.block_number,
+                block_timestamp: Utc.timestamp_opt(row.block_timestamp, 0).single()?,
+                tx_hash: row.transaction_hash.parse().ok()?,
+                tx_idx: row.transaction_index as usize,
+                log_idx: row.log_index as usize,
+                pool_id: row.pool_id.parse().ok()?,
+                currency: row.currency.parse().ok()?,
+            })
+        })
+        .collect())
+}
+
+/// Tokens among `tokens` that already have a row written by an earlier block.
+async fn written_tokens(
+    client: &clickhouse::Client,
+    block_number: u64,
+    tokens: &[Address],
+) -> Result<HashSet<Address>> {
+    let mut addresses: Vec<String> = tokens.iter().map(|token| token.to_checksum(Some(1))).collect();
+    addresses.sort_unstable();
+    addresses.dedup();
+
+    let rows = client
+        .query("SELECT DISTINCT address FROM tokens WHERE has(?, address) AND block_number < ?")
+        .bind(addresses)
+        .bind(block_number)
+        .fetch_all::<TokenAddress>()
+        .await?;
+
+    Ok(rows.iter().filter_map(|row| row.address.parse().ok()).collect())
+}
+
+/// Read-only `eth_call`s at a historical block. Implemented by the node's `EthApi`; absent
+/// when blocks are replayed without a node, in which case token metadata comes from the
+/// `tokens` table only.
+pub trait ContractCaller: Send + Sync {
+    fn call_contract(&self, to: Address, input: Vec<u8>, block_id: BlockId) -> BoxFuture<'_, Option<Bytes>>;
+}
+
+impl<EthApi> ContractCaller for EthApi
+where
+    EthApi: FullEthApi + EthApiTypes,
+    <EthApi as EthApiTypes>::NetworkTypes: RpcTypes + Network,
+    <<EthApi as EthApiTypes>::NetworkTypes as RpcTypes>::TransactionRequest: Default + TransactionBuilder<<EthApi as EthApiTypes>::NetworkTypes>,
+{
+    fn call_contract(&self, to: Address, input: Vec<u8>, block_id: BlockId) -> BoxFuture<'_, Option<Bytes>> {
+        Box::pin(async move {
+            let request = <<EthApi as EthApiTypes>::NetworkTypes as RpcTypes>::TransactionRequest::default()
+                .with_to(to)
+                .with_input(input);
+
+            match EthCall::call(self, request, Some(block_id), EvmOverrides::default()).await {
+                Ok(output) if !output.is_empty() => Some(output),
+                Ok(_) => None,
+                Err(e) => {
+                    debug!("eth_call to token {} failed: {}", to, e);
+                    None
+                }
+            }
+        })
+    }
+}
+
+/// Decodes a `string` return value, falling back to the `bytes32` encoding used by
+/// tokens such as MKR.
+fn decode_string(output: &[u8]) -> String {
+    if let Ok(value) = IERC20Metadata::symbolCall::abi_decode_returns(output) {
+        return value;
+    }
+
+    let word = &output[..output.len().min(32)];
+    let end = word.iter().position(|b| *b == 0).unwrap_or(word.len());
+    String::from_utf8_lossy(&word[..end]).into_owned()
+}
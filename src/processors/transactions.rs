@@ -0,0 +1,70 @@
+use crate::values;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use alloy::primitives::{address, Address};
+use alloy_consensus::Transaction;
+use alloy_eips::Typed2718;
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+
+const UNIV4_FACTORY_CONTRACT_ADDRESS: Address = address!("0x000000000004444c5dc75cB358380D2e3dE08A90");
+const UNIV4_POSITION_MANAGER_ADDRESS: Address = address!("0xbD216513d74C8cf14cf4747E6AaA6420FF64ee9e");
+const CHAIN_ID: u32 = 1;
+
+/// Writes one row per transaction that emitted a PoolManager or PositionManager event, with
+/// its signer, recipient, gas accounting and the block's fee recipient, so events can be
+/// joined to EOAs, routers and builders on `transaction_hash`. `gas_used` is the difference
+/// between consecutive cumulative receipt gas, and `priority_fee_per_gas` the tip actually
+/// paid above the base fee.
+pub async fn process_uni_v4_transactions(
+    block_data: &EthereumBlockData,
+    _components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+    let base_fee = block.base_fee_per_gas;
+
+    let mut previous_cumulative_gas = 0u64;
+    let transactions = block.body().transactions.iter().zip(block.senders().iter());
+    for (tx_idx, ((tx, from), receipt)) in transactions.zip(receipts.iter()).enumerate() {
+        let gas_used = receipt.cumulative_gas_used.saturating_sub(previous_cumulative_gas);
+        previous_cumulative_gas = receipt.cumulative_gas_used;
+
+        let v4_log_count = receipt
+            .logs
+            .iter()
+            .filter(|log| log.address == UNIV4_FACTORY_CONTRACT_ADDRESS || log.address == UNIV4_POSITION_MANAGER_ADDRESS)
+            .count();
+        if v4_log_count == 0 { continue; }
+
+        let method_id = tx.input().get(..4).map(|selector| selector.to_vec()).unwrap_or_default();
+        writer.write_record(values![
+            CHAIN_ID as i64,
+            block_number as i64,
+            block_timestamp,
+            tx.hash(),
+            tx_idx as i64,
+            from,
+            tx.to().unwrap_or(Address::ZERO),
+            tx.value(),
+            tx.nonce() as i64,
+            tx.ty() as i64,
+            method_id,
+            receipt.success,
+            tx.gas_limit() as i64,
+            gas_used as i64,
+            tx.effective_gas_price(base_fee),
+            tx.max_fee_per_gas(),
+            tx.max_priority_fee_per_gas().unwrap_or_default(),
+            tx.effective_tip_per_gas(base_fee.unwrap_or_default()).unwrap_or_default(),
+            base_fee.unwrap_or_default() as i64,
+            block.beneficiary,
+            v4_log_count as i64,
+        ]);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,282 @@
+use crate::values;
+use crate::config::CONFIG;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::processors::tokens::ContractCaller;
+use crate::storage::reader::{registered_pools, remember_registered_pool, remember_unregistered_pool};
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use alloy::{sol, sol_types::{SolCall, SolEvent}, primitives::{address, b256, keccak256, Address, FixedBytes, TxHash, B256}};
+use alloy_consensus::Transaction;
+use alloy_rpc_types::BlockId;
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use std::collections::HashMap;
+use tracing::debug;
+
+const UNIV2_FACTORY_CONTRACT_ADDRESS: Address = address!("0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f");
+const UNIV2_PAIR_INIT_CODE_HASH: B256 = b256!("0x96e8ac4277198ff8b6f785478aa9a39f403cb768dd02cbee326c3e7da348845f");
+const REGISTRY: &str = "uni_v2_pools";
+const CHAIN_ID: u32 = 1;
+/// Every v2 pair charges 0.3%, in pips.
+const UNIV2_FEE: u32 = 3000;
+
+sol! {
+    event PairCreated(address indexed token0, address indexed token1, address pair, uint256 pairCount);
+    event Swap(
+        address indexed sender,
+        uint256 amount0In,
+        uint256 amount1In,
+        uint256 amount0Out,
+        uint256 amount1Out,
+        address indexed to
+    );
+    event Sync(uint112 reserve0, uint112 reserve1);
+
+    interface IUniswapV2Pair {
+        function token0() external view returns (address);
+        function token1() external view returns (address);
+    }
+}
+
+/// A log with one of the requested signatures, kept until its emitter is known to be a pair.
+struct PairLog<'a> {
+    tx_idx: usize,
+    tx_hash: TxHash,
+    tx_to: Option<Address>,
+    origin: Address,
+    log_idx: usize,
+    log: &'a alloy::primitives::Log,
+}
+
+/// Writes one row per v2 factory `PairCreated` event in the leading `uni_v4_pools` columns,
+/// with the pair address as its 32-byte `pool_id`. Pairs have a fixed 0.3% fee and no
+/// ticks, hooks or initial price, which are written as zeros.
+pub async fn process_uni_v2_pools(
+    block_data: &EthereumBlockData,
+    _components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV2_FACTORY_CONTRACT_ADDRESS { continue; }
+            if log.topics().get(0) != Some(&PairCreated::SIGNATURE_HASH) { continue; }
+
+            match PairCreated::decode_raw_log(log.topics(), &log.data.data) {
+                Ok(evt) => {
+                    writer.write_record(values![
+                        CHAIN_ID as i64,
+                        block_number as i64,
+                        block_timestamp,
+                        tx.hash(),
+                        tx_idx as i64,
+                        log_idx as i64,
+                        log.address,
+                        evt.pair.into_word(),
+                        evt.token0,
+                        evt.token1,
+                        UNIV2_FEE as i64,
+                        0i64,
+                        Address::ZERO,
+                        0i64,
+                        0i64,
+                        evt.pair,
+                    ]);
+                }
+                Err(e) => { debug!("Failed to decode univ2 pair creation event: {:?}", e); }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes one row per v2 pair `Swap` in the `uni_v4_swaps` layout. `amount0`/`amount1` are
+/// the net amounts from the swapper's side (`out - in`, negative is paid), the price,
+/// liquidity and tick columns are zero, and `router`/`aggregator` classify the
+/// transaction's `to`.
+pub async fn process_uni_v2_swaps(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let logs = pair_logs(block_data, &[Swap::SIGNATURE_HASH]);
+    if logs.is_empty() { return Ok(()); }
+    let pairs = known_pairs(&components, block_number, &logs).await?;
+
+    for pair_log in logs {
+        let Some(&fee) = pairs.get(&pair_log.log.address) else { continue; };
+        let log = pair_log.log;
+
+        match Swap::decode_raw_log(log.topics(), &log.data.data) {
+            Ok(evt) => {
+                let amount0 = evt.amount0Out.saturating_to::<u128>() as i128 - evt.amount0In.saturating_to::<u128>() as i128;
+                let amount1 = evt.amount1Out.saturating_to::<u128>() as i128 - evt.amount1In.saturating_to::<u128>() as i128;
+                let router = pair_log.tx_to.filter(|to| CONFIG.routers.contains(to)).unwrap_or_default();
+                let aggregator = pair_log.tx_to.filter(|to| CONFIG.aggregators.contains(to)).unwrap_or_default();
+
+                let event_id = format!("{}#{}", pair_log.tx_hash, pair_log.log_idx);
+                writer.write_record(values![
+                    CHAIN_ID as i64,
+                    block_number as i64,
+                    block_timestamp,
+                    pair_log.tx_hash,
+                    pair_log.tx_idx as i64,
+                    pair_log.log_idx as i64,
+                    log.address,
+                    event_id,
+                    log.address.into_word(),
+                    evt.sender,
+                    amount0,
+                    amount1,
+                    0i64,
+                    0i64,
+                    0i64,
+                    fee as i64,
+                    pair_log.origin,
+                    router,
+                    aggregator,
+                    evt.to,
+                ]);
+            }
+            Err(e) => { debug!("Failed to decode univ2 swap event: {:?}", e); }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes one row per v2 pair `Sync`: the pair's reserves after every balance change.
+pub async fn process_uni_v2_syncs(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let logs = pair_logs(block_data, &[Sync::SIGNATURE_HASH]);
+    if logs.is_empty() { return Ok(()); }
+    let pairs = known_pairs(&components, block_number, &logs).await?;
+
+    for pair_log in logs {
+        if !pairs.contains_key(&pair_log.log.address) { continue; }
+        let log = pair_log.log;
+
+        match Sync::decode_raw_log(log.topics(), &log.data.data) {
+            Ok(evt) => {
+                let event_id = format!("{}#{}", pair_log.tx_hash, pair_log.log_idx);
+                writer.write_record(values![
+                    CHAIN_ID as i64,
+                    block_number as i64,
+                    block_timestamp,
+                    pair_log.tx_hash,
+                    pair_log.tx_idx as i64,
+                    pair_log.log_idx as i64,
+                    log.address,
+                    event_id,
+                    log.address.into_word(),
+                    evt.reserve0,
+                    evt.reserve1,
+                ]);
+            }
+            Err(e) => { debug!("Failed to decode univ2 sync event: {:?}", e); }
+        }
+    }
+
+    Ok(())
+}
+
+/// Which emitters of `logs` are v2 pairs, with their fee in pips. Pairs created before v2
+/// indexing was enabled are not in `uni_v2_pools` and are verified through `eth_call`
+/// instead; without it they are skipped.
+async fn known_pairs(
+    components: &ProcessingComponents,
+    block_number: u64,
+    logs: &[PairLog<'_>],
+) -> Result<HashMap<Address, u32>> {
+    let addresses: Vec<Address> = logs.iter().map(|l| l.log.address).collect();
+    let (mut pairs, unknown) = registered_pools(&components.client, REGISTRY, &addresses).await?;
+    let Some(eth_api) = components.eth_api.as_deref() else { return Ok(pairs); };
+
+    for address in unknown {
+        match is_factory_pair(eth_api, address, block_number).await {
+            Some(true) => {
+                remember_registered_pool(REGISTRY, address, UNIV2_FEE);
+                pairs.insert(address, UNIV2_FEE);
+            }
+            Some(false) => remember_unregistered_pool(REGISTRY, address),
+            None => debug!("Could not check whether {} is a univ2 pair", address),
+        }
+    }
+    Ok(pairs)
+}
+
+/// Whether the v2 factory deployed `address`, i.e. whether its `token0()` and `token1()`
+/// give back its address as the factory's CREATE2 address. `None` when a call fails, so the
+/// address is checked again later.
+async fn is_factory_pair(eth_api: &dyn ContractCaller, address: Address, block_number: u64) -> Option<bool> {
+    let block_id = BlockId::from(block_number);
+    let token0 = eth_api.call_contract(address, IUniswapV2Pair::token0Call {}.abi_encode(), block_id).await?;
+    let token1 = eth_api.call_contract(address, IUniswapV2Pair::token1Call {}.abi_encode(), block_id).await?;
+
+    let token0 = IUniswapV2Pair::token0Call::abi_decode_returns(&token0).ok()?;
+    let token1 = IUniswapV2Pair::token1Call::abi_decode_returns(&token1).ok()?;
+
+    Some(pair_address(token0, token1) == address)
+}
+
+/// The address the v2 factory deploys the `token0`/`token1` pair at.
+fn pair_address(token0: Address, token1: Address) -> Address {
+    let salt = keccak256([token0.as_slice(), token1.as_slice()].concat());
+    UNIV2_FACTORY_CONTRACT_ADDRESS.create2(salt, UNIV2_PAIR_INIT_CODE_HASH)
+}
+
+/// Logs of the block whose first topic is one of `signatures`, to be filtered against the
+/// pair registry. Pairs created by the factory in this block are registered first.
+fn pair_logs<'a>(block_data: &'a EthereumBlockData, signatures: &[FixedBytes<32>]) -> Vec<PairLog<'a>> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+
+    let mut logs = Vec::new();
+    let transactions = block.body().transactions.iter().zip(block.senders().iter());
+    for (tx_idx, ((tx, origin), receipt)) in transactions.zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            let Some(topic) = log.topics().get(0) else { continue; };
+
+            if log.address == UNIV2_FACTORY_CONTRACT_ADDRESS && *topic == PairCreated::SIGNATURE_HASH {
+                match PairCreated::decode_raw_log(log.topics(), &log.data.data) {
+                    Ok(evt) => remember_registered_pool(REGISTRY, evt.pair, UNIV2_FEE),
+                    Err(e) => { debug!("Failed to decode univ2 pair creation event: {:?}", e); }
+                }
+                continue;
+            }
+
+            if !signatures.contains(topic) { continue; }
+            logs.push(PairLog { tx_idx, tx_hash: *tx.hash(), tx_to: tx.to(), origin: *origin, log_idx, log });
+        }
+    }
+    logs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDC: Address = address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+    const WETH: Address = address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+
+    #[test]
+    fn pair_address_matches_the_factory() {
+        assert_eq!(pair_address(USDC, WETH), address!("0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc"));
+        assert_ne!(pair_address(WETH, USDC), address!("0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc"));
+    }
+}
@@ -0,0 +1,378 @@
+use crate::values;
+use crate::config::CONFIG;
+use crate::indexer::{ProcessingComponents, EthereumBlockData};
+use crate::processors::tokens::ContractCaller;
+use crate::storage::reader::{registered_pools, remember_registered_pool, remember_unregistered_pool};
+use crate::storage::writer::ClickhouseWriter as DbWriter;
+use alloy::{sol, sol_types::{SolCall, SolEvent, SolValue}, primitives::{address, b256, keccak256, Address, FixedBytes, TxHash, B256, U256}};
+use alloy_consensus::Transaction;
+use alloy_rpc_types::BlockId;
+use eyre::Result;
+use chrono::{Utc, TimeZone};
+use std::collections::HashMap;
+use tracing::debug;
+
+const UNIV3_FACTORY_CONTRACT_ADDRESS: Address = address!("0x1F98431c8aD98523631AE4a59f267346ea31F984");
+const UNIV3_POOL_INIT_CODE_HASH: B256 = b256!("0xe34f199b19b2b4f47f68442619d555527d244f78a3297ea89325f843f87b8b54");
+const REGISTRY: &str = "uni_v3_pools";
+const CHAIN_ID: u32 = 1;
+
+sol! {
+    event PoolCreated(
+        address indexed token0,
+        address indexed token1,
+        uint24 indexed fee,
+        int24 tickSpacing,
+        address pool
+    );
+    event Swap(
+        address indexed sender,
+        address indexed recipient,
+        int256 amount0,
+        int256 amount1,
+        uint160 sqrtPriceX96,
+        uint128 liquidity,
+        int24 tick
+    );
+    event Mint(
+        address sender,
+        address indexed owner,
+        int24 indexed tickLower,
+        int24 indexed tickUpper,
+        uint128 amount,
+        uint256 amount0,
+        uint256 amount1
+    );
+    event Burn(
+        address indexed owner,
+        int24 indexed tickLower,
+        int24 indexed tickUpper,
+        uint128 amount,
+        uint256 amount0,
+        uint256 amount1
+    );
+    event Collect(
+        address indexed owner,
+        address recipient,
+        int24 indexed tickLower,
+        int24 indexed tickUpper,
+        uint128 amount0,
+        uint128 amount1
+    );
+
+    interface IUniswapV3Pool {
+        function token0() external view returns (address);
+        function token1() external view returns (address);
+        function fee() external view returns (uint24);
+    }
+}
+
+/// A log with one of the requested signatures, kept until its emitter is known to be a pool.
+struct PoolLog<'a> {
+    tx_idx: usize,
+    tx_hash: TxHash,
+    tx_to: Option<Address>,
+    origin: Address,
+    log_idx: usize,
+    log: &'a alloy::primitives::Log,
+}
+
+/// Writes one row per v3 factory `PoolCreated` event. The leading columns match
+/// `uni_v4_pools`, with the pool address as its 32-byte `pool_id`; v3 pools have no hooks
+/// and are initialized later, so `hooks` and the initial price columns hold zeros.
+pub async fn process_uni_v3_pools(
+    block_data: &EthereumBlockData,
+    _components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    for (tx_idx, (tx, receipt)) in block.body().transactions.iter().zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            if log.address != UNIV3_FACTORY_CONTRACT_ADDRESS { continue; }
+            if log.topics().get(0) != Some(&PoolCreated::SIGNATURE_HASH) { continue; }
+
+            match PoolCreated::decode_raw_log(log.topics(), &log.data.data) {
+                Ok(evt) => {
+                    writer.write_record(values![
+                        CHAIN_ID as i64,
+                        block_number as i64,
+                        block_timestamp,
+                        tx.hash(),
+                        tx_idx as i64,
+                        log_idx as i64,
+                        log.address,
+                        evt.pool.into_word(),
+                        evt.token0,
+                        evt.token1,
+                        evt.fee,
+                        evt.tickSpacing,
+                        Address::ZERO,
+                        0i64,
+                        0i64,
+                        evt.pool,
+                    ]);
+                }
+                Err(e) => { debug!("Failed to decode univ3 pool creation event: {:?}", e); }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes one row per v3 pool `Swap` in the `uni_v4_swaps` layout. Amounts are flipped to
+/// the v4 convention (negative is paid by the swapper), `fee` is the pool's fee tier and
+/// `router`/`aggregator` classify the transaction's `to`.
+pub async fn process_uni_v3_swaps(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let logs = pool_logs(block_data, &[Swap::SIGNATURE_HASH]);
+    if logs.is_empty() { return Ok(()); }
+    let pools = known_pools(&components, block_number, &logs).await?;
+
+    for pool_log in logs {
+        let Some(&fee) = pools.get(&pool_log.log.address) else { continue; };
+        let log = pool_log.log;
+
+        match Swap::decode_raw_log(log.topics(), &log.data.data) {
+            Ok(evt) => {
+                let (Ok(amount0), Ok(amount1)) = (i128::try_from(-evt.amount0), i128::try_from(-evt.amount1)) else {
+                    debug!("Swap amounts out of range for {}#{}", pool_log.tx_hash, pool_log.log_idx);
+                    continue;
+                };
+                let router = pool_log.tx_to.filter(|to| CONFIG.routers.contains(to)).unwrap_or_default();
+                let aggregator = pool_log.tx_to.filter(|to| CONFIG.aggregators.contains(to)).unwrap_or_default();
+
+                let event_id = format!("{}#{}", pool_log.tx_hash, pool_log.log_idx);
+                writer.write_record(values![
+                    CHAIN_ID as i64,
+                    block_number as i64,
+                    block_timestamp,
+                    pool_log.tx_hash,
+                    pool_log.tx_idx as i64,
+                    pool_log.log_idx as i64,
+                    log.address,
+                    event_id,
+                    log.address.into_word(),
+                    evt.sender,
+                    amount0,
+                    amount1,
+                    evt.sqrtPriceX96,
+                    evt.liquidity,
+                    evt.tick,
+                    fee as i64,
+                    pool_log.origin,
+                    router,
+                    aggregator,
+                    evt.recipient,
+                ]);
+            }
+            Err(e) => { debug!("Failed to decode univ3 swap event: {:?}", e); }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes one row per v3 pool `Mint` (positive `liquidity_delta`) and `Burn` (negative) in
+/// the `uni_v4_modify_liquidity` layout, followed by the position owner and token amounts.
+/// v3 positions have no salt, so `salt` is zero.
+pub async fn process_uni_v3_modify_liquidity(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let logs = pool_logs(block_data, &[Mint::SIGNATURE_HASH, Burn::SIGNATURE_HASH]);
+    if logs.is_empty() { return Ok(()); }
+    let pools = known_pools(&components, block_number, &logs).await?;
+
+    for pool_log in logs {
+        if !pools.contains_key(&pool_log.log.address) { continue; }
+        let log = pool_log.log;
+
+        let decoded = if log.topics().get(0) == Some(&Mint::SIGNATURE_HASH) {
+            Mint::decode_raw_log(log.topics(), &log.data.data)
+                .map(|evt| (evt.sender, evt.owner, evt.tickLower, evt.tickUpper, evt.amount as i128, evt.amount0, evt.amount1))
+        } else {
+            Burn::decode_raw_log(log.topics(), &log.data.data)
+                .map(|evt| (evt.owner, evt.owner, evt.tickLower, evt.tickUpper, -(evt.amount as i128), evt.amount0, evt.amount1))
+        };
+
+        match decoded {
+            Ok((sender, owner, tick_lower, tick_upper, liquidity_delta, amount0, amount1)) => {
+                let event_id = format!("{}#{}", pool_log.tx_hash, pool_log.log_idx);
+                writer.write_record(values![
+                    CHAIN_ID as i64,
+                    block_number as i64,
+                    block_timestamp,
+                    pool_log.tx_hash,
+                    pool_log.tx_idx as i64,
+                    pool_log.log_idx as i64,
+                    log.address,
+                    event_id,
+                    log.address.into_word(),
+                    sender,
+                    tick_lower,
+                    tick_upper,
+                    liquidity_delta,
+                    B256::ZERO,
+                    owner,
+                    amount0,
+                    amount1,
+                ]);
+            }
+            Err(e) => { debug!("Failed to decode univ3 modify liquidity event: {:?}", e); }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes one row per v3 pool `Collect`: fees and burned liquidity withdrawn from a position.
+pub async fn process_uni_v3_collects(
+    block_data: &EthereumBlockData,
+    components: ProcessingComponents,
+    writer: &mut DbWriter,
+) -> Result<()> {
+    let block = &block_data.0;
+    let block_number = block.num_hash().number;
+    let block_timestamp = Utc.timestamp_opt(block.timestamp as i64, 0).single().unwrap_or_else(Utc::now);
+
+    let logs = pool_logs(block_data, &[Collect::SIGNATURE_HASH]);
+    if logs.is_empty() { return Ok(()); }
+    let pools = known_pools(&components, block_number, &logs).await?;
+
+    for pool_log in logs {
+        if !pools.contains_key(&pool_log.log.address) { continue; }
+        let log = pool_log.log;
+
+        match Collect::decode_raw_log(log.topics(), &log.data.data) {
+            Ok(evt) => {
+                let event_id = format!("{}#{}", pool_log.tx_hash, pool_log.log_idx);
+                writer.write_record(values![
+                    CHAIN_ID as i64,
+                    block_number as i64,
+                    block_timestamp,
+                    pool_log.tx_hash,
+                    pool_log.tx_idx as i64,
+                    pool_log.log_idx as i64,
+                    log.address,
+                    event_id,
+                    log.address.into_word(),
+                    evt.owner,
+                    evt.recipient,
+                    evt.tickLower,
+                    evt.tickUpper,
+                    evt.amount0,
+                    evt.amount1,
+                ]);
+            }
+            Err(e) => { debug!("Failed to decode univ3 collect event: {:?}", e); }
+        }
+    }
+
+    Ok(())
+}
+
+/// Which emitters of `logs` are v3 pools, with their fee in pips. Pools created before v3
+/// indexing was enabled are not in `uni_v3_pools` and are verified through `eth_call`
+/// instead; without it they are skipped.
+async fn known_pools(
+    components: &ProcessingComponents,
+    block_number: u64,
+    logs: &[PoolLog<'_>],
+) -> Result<HashMap<Address, u32>> {
+    let addresses: Vec<Address> = logs.iter().map(|l| l.log.address).collect();
+    let (mut pools, unknown) = registered_pools(&components.client, REGISTRY, &addresses).await?;
+    let Some(eth_api) = components.eth_api.as_deref() else { return Ok(pools); };
+
+    for address in unknown {
+        match factory_pool_fee(eth_api, address, block_number).await {
+            Some(Some(fee)) => {
+                remember_registered_pool(REGISTRY, address, fee);
+                pools.insert(address, fee);
+            }
+            Some(None) => remember_unregistered_pool(REGISTRY, address),
+            None => debug!("Could not check whether {} is a univ3 pool", address),
+        }
+    }
+    Ok(pools)
+}
+
+/// The fee of `address` if the v3 factory deployed it, i.e. if its `token0()`, `token1()`
+/// and `fee()` give back its address as the factory's CREATE2 address. `None` when a call
+/// fails, so the address is checked again later.
+async fn factory_pool_fee(eth_api: &dyn ContractCaller, address: Address, block_number: u64) -> Option<Option<u32>> {
+    let block_id = BlockId::from(block_number);
+    let token0 = eth_api.call_contract(address, IUniswapV3Pool::token0Call {}.abi_encode(), block_id).await?;
+    let token1 = eth_api.call_contract(address, IUniswapV3Pool::token1Call {}.abi_encode(), block_id).await?;
+    let fee = eth_api.call_contract(address, IUniswapV3Pool::feeCall {}.abi_encode(), block_id).await?;
+
+    let token0 = IUniswapV3Pool::token0Call::abi_decode_returns(&token0).ok()?;
+    let token1 = IUniswapV3Pool::token1Call::abi_decode_returns(&token1).ok()?;
+    let fee = IUniswapV3Pool::feeCall::abi_decode_returns(&fee).ok()?.to::<u32>();
+
+    Some((pool_address(token0, token1, fee) == address).then_some(fee))
+}
+
+/// The address the v3 factory deploys the `token0`/`token1` pool with `fee` at.
+fn pool_address(token0: Address, token1: Address, fee: u32) -> Address {
+    let salt = keccak256((token0, token1, U256::from(fee)).abi_encode());
+    UNIV3_FACTORY_CONTRACT_ADDRESS.create2(salt, UNIV3_POOL_INIT_CODE_HASH)
+}
+
+/// Logs of the block whose first topic is one of `signatures`, to be filtered against the
+/// pool registry. Pools created by the factory in this block are registered first.
+fn pool_logs<'a>(block_data: &'a EthereumBlockData, signatures: &[FixedBytes<32>]) -> Vec<PoolLog<'a>> {
+    let block = &block_data.0;
+    let receipts = &block_data.1;
+
+    let mut logs = Vec::new();
+    let transactions = block.body().transactions.iter().zip(block.senders().iter());
+    for (tx_idx, ((tx, origin), receipt)) in transactions.zip(receipts.iter()).enumerate() {
+        for (log_idx, log) in receipt.logs.iter().enumerate() {
+            let Some(topic) = log.topics().get(0) else { continue; };
+
+            if log.address == UNIV3_FACTORY_CONTRACT_ADDRESS && *topic == PoolCreated::SIGNATURE_HASH {
+                match PoolCreated::decode_raw_log(log.topics(), &log.data.data) {
+                    Ok(evt) => remember_registered_pool(REGISTRY, evt.pool, evt.fee.to::<u32>()),
+                    Err(e) => { debug!("Failed to decode univ3 pool creation event: {:?}", e); }
+                }
+                continue;
+            }
+
+            if !signatures.contains(topic) { continue; }
+            logs.push(PoolLog { tx_idx, tx_hash: *tx.hash(), tx_to: tx.to(), origin: *origin, log_idx, log });
+        }
+    }
+    logs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDC: Address = address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+    const WETH: Address = address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+
+    #[test]
+    fn pool_address_matches_the_factory() {
+        assert_eq!(pool_address(USDC, WETH, 500), address!("0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640"));
+        assert_eq!(pool_address(USDC, WETH, 3000), address!("0x8ad599c3A0ff1De082011EFDDc58f1908eb6e6D8"));
+        assert_ne!(pool_address(WETH, USDC, 500), address!("0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640"));
+    }
+}
@@ -1,11 +1,21 @@
 mod types;
 mod tables;
+mod views;
 
-pub use types::{Table, Column};
+pub use types::{Table, Column, MaterializedView};
 pub use tables::definitions;
 
 lazy_static::lazy_static! {
-    pub static ref TABLES: Vec<Table> = definitions();
+    pub static ref TABLES: Vec<Table> = {
+        let mut tables = definitions();
+        tables.extend(crate::abi::tables());
+        tables
+    };
+    pub static ref VIEWS: Vec<MaterializedView> = views::definitions();
 }
 
 pub fn get(name: &str) -> Option<Table> { TABLES.iter().find(|t| t.name == name).cloned() }
+
+pub fn views_for(source: &str) -> impl Iterator<Item = &'static MaterializedView> + '_ {
+    VIEWS.iter().filter(move |v| v.source == source)
+}
@@ -20,6 +20,21 @@ pub fn definitions() -> Vec<Table> {
                 Column { name: "hooks", sql_type: "String", nullable: false, primary_key: false },
                 Column { name: "initial_sqrt_price_x96", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
                 Column { name: "initial_tick", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "before_initialize", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "after_initialize", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "before_add_liquidity", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "after_add_liquidity", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "before_remove_liquidity", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "after_remove_liquidity", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "before_swap", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "after_swap", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "before_donate", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "after_donate", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "before_swap_return_delta", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "after_swap_return_delta", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "after_add_liquidity_return_delta", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "after_remove_liquidity_return_delta", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "is_dynamic_fee", sql_type: "Bool", nullable: false, primary_key: false },
             ],
             indexes: vec![],
             partition_by: Some("toDate(block_timestamp)"),
@@ -43,6 +58,9 @@ pub fn definitions() -> Vec<Table> {
                 Column { name: "liquidity", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
                 Column { name: "tick", sql_type: "Int32", nullable: false, primary_key: false },
                 Column { name: "fee", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "origin", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "router", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "aggregator", sql_type: "String", nullable: false, primary_key: false },
             ],
             indexes: vec![],
             partition_by: Some("toDate(block_timestamp)"),
@@ -87,5 +105,707 @@ pub fn definitions() -> Vec<Table> {
             indexes: vec![],
             partition_by: Some("toDate(block_timestamp)"),
         },
+        Table {
+            name: "uni_v4_ticks",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: true },
+                Column { name: "tick", sql_type: "Int32", nullable: false, primary_key: true },
+                Column { name: "liquidity_gross", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "liquidity_net", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "liquidity_net_delta", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_positions",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "position_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "owner", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "tick_lower", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "tick_upper", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "salt", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "liquidity_delta", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "liquidity", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "is_open", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "opened_at", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "closed_at", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_position_nfts",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_type", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "token_id", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "from_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "to_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "owner", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "subscriber", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "position_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "tick_lower", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "tick_upper", sql_type: "Int32", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_hooks",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "hook_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "first_seen_block", sql_type: "UInt64", nullable: false, primary_key: false },
+                Column { name: "first_seen_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "pool_count", sql_type: "UInt64", nullable: false, primary_key: false },
+                Column { name: "permissions", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "before_initialize", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "after_initialize", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "before_add_liquidity", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "after_add_liquidity", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "before_remove_liquidity", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "after_remove_liquidity", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "before_swap", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "after_swap", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "before_donate", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "after_donate", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "before_swap_return_delta", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "after_swap_return_delta", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "after_add_liquidity_return_delta", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "after_remove_liquidity_return_delta", sql_type: "Bool", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_hook_calls",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "trace_address", sql_type: "String", nullable: false, primary_key: true },
+                Column { name: "call_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "hook_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "function_name", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "selector", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "sender", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "gas_used", sql_type: "UInt64", nullable: false, primary_key: false },
+                Column { name: "success", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "return_delta0", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "return_delta1", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "lp_fee_override", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "fee_overridden", sql_type: "Bool", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_claims_transfers",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "caller", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "from_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "to_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "currency", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "amount", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_claims_approvals",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_type", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "owner", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "spender", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "currency", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "amount", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "approved", sql_type: "Bool", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_claims_balances",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "account", sql_type: "String", nullable: false, primary_key: true },
+                Column { name: "currency", sql_type: "String", nullable: false, primary_key: true },
+                Column { name: "delta", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "balance", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_protocol_fees",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "protocol_fee", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "protocol_fee_zero_for_one", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "protocol_fee_one_for_zero", sql_type: "UInt32", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_protocol_fee_controllers",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "protocol_fee_controller", sql_type: "String", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_fee_changes",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "source", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "is_dynamic_fee", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "previous_fee", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "fee", sql_type: "UInt32", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "tokens",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "address", sql_type: "String", nullable: false, primary_key: true },
+                Column { name: "name", sql_type: "String", nullable: true, primary_key: false },
+                Column { name: "symbol", sql_type: "String", nullable: true, primary_key: false },
+                Column { name: "decimals", sql_type: "UInt32", nullable: true, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: None,
+        },
+        Table {
+            name: "uni_v4_swaps_enriched",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "currency0", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "currency1", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "amount0_adjusted", sql_type: "Float64", nullable: false, primary_key: false },
+                Column { name: "amount1_adjusted", sql_type: "Float64", nullable: false, primary_key: false },
+                Column { name: "price", sql_type: "Float64", nullable: false, primary_key: false },
+                Column { name: "eth_usd_price", sql_type: "Float64", nullable: true, primary_key: false },
+                Column { name: "volume_usd", sql_type: "Float64", nullable: true, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_fees",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "zero_for_one", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "fee", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "protocol_fee", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "fee_amount0", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "fee_amount1", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "protocol_fee_amount0", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "protocol_fee_amount1", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "lp_fee_amount0", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "lp_fee_amount1", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "liquidity", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "fee_growth_global0_x128", sql_type: "UInt256", nullable: false, primary_key: false },
+                Column { name: "fee_growth_global1_x128", sql_type: "UInt256", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_position_fees",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "position_id", sql_type: "FixedString(66)", nullable: false, primary_key: true },
+                Column { name: "owner", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "tick_lower", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "tick_upper", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "liquidity", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "fee_amount0", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "fee_amount1", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_sandwiches",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "attacker", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "attacker_contract", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "zero_for_one", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "frontrun_transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "frontrun_transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "frontrun_log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "backrun_transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "backrun_transaction_index", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "backrun_log_index", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "victim_count", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "victim_transaction_hashes", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "profit_amount0", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "profit_amount1", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_jit_liquidity",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "position_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "provider", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "owner", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "tick_lower", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "tick_upper", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "liquidity", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "add_transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "add_transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "add_log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "remove_transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "remove_transaction_index", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "remove_log_index", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "victim_count", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "victim_transaction_hashes", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "fee_amount0", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "fee_amount1", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_transactions",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "from_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "to_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "value", sql_type: "UInt256", nullable: false, primary_key: false },
+                Column { name: "nonce", sql_type: "UInt64", nullable: false, primary_key: false },
+                Column { name: "transaction_type", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "method_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "success", sql_type: "Bool", nullable: false, primary_key: false },
+                Column { name: "gas_limit", sql_type: "UInt64", nullable: false, primary_key: false },
+                Column { name: "gas_used", sql_type: "UInt64", nullable: false, primary_key: false },
+                Column { name: "effective_gas_price", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "max_fee_per_gas", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "max_priority_fee_per_gas", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "priority_fee_per_gas", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "base_fee_per_gas", sql_type: "UInt64", nullable: false, primary_key: false },
+                Column { name: "block_beneficiary", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "v4_log_count", sql_type: "UInt32", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_routes",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "route_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "unlock_trace_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "origin", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "token_in", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "token_out", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "amount_in", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "amount_out", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "hop_count", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "pool_path", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "token_path", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "first_log_index", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "last_log_index", sql_type: "UInt32", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_settlements",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "trace_address", sql_type: "String", nullable: false, primary_key: true },
+                Column { name: "call_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "unlock_trace_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "locker", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "caller", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "action", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "currency", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "account", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "amount", sql_type: "UInt256", nullable: false, primary_key: false },
+                Column { name: "delta", sql_type: "Int256", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v3_pools",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "currency0", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "currency1", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "fee", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "tick_spacing", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "hooks", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "initial_sqrt_price_x96", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "initial_tick", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "pool_address", sql_type: "String", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v3_swaps",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "sender", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "amount0", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "amount1", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "sqrt_price_x96", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "liquidity", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "tick", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "fee", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "origin", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "router", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "aggregator", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "recipient", sql_type: "String", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v3_modify_liquidity",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "sender", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "tick_lower", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "tick_upper", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "liquidity_delta", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "salt", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "owner", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "amount0", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "amount1", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v3_collects",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "owner", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "recipient", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "tick_lower", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "tick_upper", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "amount0", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "amount1", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v2_pools",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "currency0", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "currency1", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "fee", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "tick_spacing", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "hooks", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "initial_sqrt_price_x96", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "initial_tick", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "pool_address", sql_type: "String", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v2_swaps",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "sender", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "amount0", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "amount1", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "sqrt_price_x96", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "liquidity", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "tick", sql_type: "Int32", nullable: false, primary_key: false },
+                Column { name: "fee", sql_type: "UInt32", nullable: false, primary_key: false },
+                Column { name: "origin", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "router", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "aggregator", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "recipient", sql_type: "String", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v2_syncs",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "pool_id", sql_type: "FixedString(66)", nullable: false, primary_key: false },
+                Column { name: "reserve0", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+                Column { name: "reserve1", sql_type: "Decimal(38,0)", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "abi_contracts",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "source", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "factory", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "contract_address", sql_type: "String", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_hook_logs",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "topic0", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "topic1", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "topic2", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "topic3", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "data", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_name", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "decoded_args", sql_type: "String", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_raw_logs",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "transaction_hash", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "transaction_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_index", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "log_address", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_id", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "topic0", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "topic1", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "topic2", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "topic3", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "data", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "event_name", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "decoded", sql_type: "Bool", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
+        Table {
+            name: "uni_v4_decode_failures",
+            columns: vec![
+                Column { name: "chain_id", sql_type: "UInt32", nullable: false, primary_key: true },
+                Column { name: "block_number", sql_type: "UInt64", nullable: false, primary_key: true },
+                Column { name: "block_timestamp", sql_type: "DateTime64(3, 'UTC')", nullable: false, primary_key: false },
+                Column { name: "topic0", sql_type: "String", nullable: false, primary_key: true },
+                Column { name: "event_name", sql_type: "String", nullable: false, primary_key: false },
+                Column { name: "reason", sql_type: "String", nullable: false, primary_key: true },
+                Column { name: "failures", sql_type: "UInt64", nullable: false, primary_key: false },
+                Column { name: "first_event_id", sql_type: "String", nullable: false, primary_key: false },
+            ],
+            indexes: vec![],
+            partition_by: Some("toDate(block_timestamp)"),
+        },
     ]
 }
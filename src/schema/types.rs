@@ -18,28 +18,7 @@ impl Table {
     pub fn create_table_sql(&self) -> String {
         let mut columns: Vec<String> = Vec::with_capacity(self.columns.len());
         for col in &self.columns {
-            let clickhouse_type = match col.sql_type {
-                "BIGINT" => "Int64",
-                "INTEGER" => "Int32",
-                "SMALLINT" => "Int16",
-                "TEXT" | "VARCHAR" => "String",
-                "BOOLEAN" => "Bool",
-                "DOUBLE PRECISION" => "Float64",
-                "REAL" => "Float32",
-                "TIMESTAMP WITH TIME ZONE" | "TIMESTAMP" => "DateTime",
-                "DATE" => "Date",
-                "UInt32" => "UInt32",
-                "UInt64" => "UInt64",
-                "Int32" => "Int32",
-                "Int64" => "Int64",
-                "FixedString(66)" => "FixedString(66)",
-                "FixedString(40)" => "FixedString(40)",
-                "DateTime64(3, 'UTC')" => "DateTime64(3, 'UTC')",
-                "Decimal(38,0)" => "Decimal(38, 0)",
-                _ => "String",
-            };
-            let nullable = if col.nullable { format!("Nullable({})", clickhouse_type) } else { clickhouse_type.to_string() };
-            columns.push(format!("{} {}", col.name, nullable));
+            columns.push(format!("{} {}", col.name, col.clickhouse_type()));
         }
 
         let mut primary_key_cols: Vec<String> = Vec::with_capacity(self.columns.len());
@@ -73,8 +52,81 @@ impl Table {
 
     pub fn create_index_statements(&self) -> Vec<String> { vec![] }
 
+    /// Columns added after a table was first created are not picked up by
+    /// `CREATE TABLE IF NOT EXISTS`, so every column is also ensured individually, and
+    /// nullable columns are converted in case they were created before being nullable.
+    pub fn add_column_statements(&self) -> Vec<String> {
+        let mut statements: Vec<String> = self.columns
+            .iter()
+            .map(|col| format!("ALTER TABLE {} ADD COLUMN IF NOT EXISTS {} {}", self.name, col.name, col.clickhouse_type()))
+            .collect();
+        statements.extend(
+            self.columns
+                .iter()
+                .filter(|col| col.nullable)
+                .map(|col| format!("ALTER TABLE {} MODIFY COLUMN {} {}", self.name, col.name, col.clickhouse_type())),
+        );
+        statements
+    }
+
+    /// Waits for the delete to be applied on every replica, so the next block does not read
+    /// reverted rows as its starting state.
     pub fn revert_statement(&self) -> String {
-        format!("ALTER TABLE {} DELETE WHERE block_number IN ({{}})", self.name)
+        format!("ALTER TABLE {} DELETE WHERE block_number IN ({{}}) SETTINGS mutations_sync = 2", self.name)
+    }
+
+    pub fn revert_range_statement(&self, from: u64, to: u64) -> String {
+        format!("ALTER TABLE {} DELETE WHERE block_number BETWEEN {} AND {} SETTINGS mutations_sync = 2", self.name, from, to)
+    }
+
+    pub fn count_range_statement(&self, from: u64, to: u64) -> String {
+        format!("SELECT count() FROM {} WHERE block_number BETWEEN {} AND {}", self.name, from, to)
+    }
+}
+
+impl Column {
+    pub fn clickhouse_type(&self) -> String {
+        let clickhouse_type = match self.sql_type {
+            "BIGINT" => "Int64",
+            "INTEGER" => "Int32",
+            "SMALLINT" => "Int16",
+            "TEXT" | "VARCHAR" => "String",
+            "BOOLEAN" | "Bool" => "Bool",
+            "DOUBLE PRECISION" | "Float64" => "Float64",
+            "REAL" => "Float32",
+            "TIMESTAMP WITH TIME ZONE" | "TIMESTAMP" => "DateTime",
+            "DATE" => "Date",
+            "UInt32" => "UInt32",
+            "UInt64" => "UInt64",
+            "Int32" => "Int32",
+            "Int64" => "Int64",
+            "FixedString(66)" => "FixedString(66)",
+            "FixedString(40)" => "FixedString(40)",
+            "DateTime64(3, 'UTC')" => "DateTime64(3, 'UTC')",
+            "Decimal(38,0)" => "Decimal(38, 0)",
+            "UInt256" => "UInt256",
+            "Int256" => "Int256",
+            _ => "String",
+        };
+        if self.nullable { format!("Nullable({})", clickhouse_type) } else { clickhouse_type.to_string() }
     }
 }
 
+/// A materialized view fed by inserts into the `source` table. Its target table keeps
+/// `block_number` so reverted blocks can be deleted from it like from any other table.
+#[derive(Debug, Clone)]
+pub struct MaterializedView {
+    pub source: &'static str,
+    pub target: String,
+    pub create_statements: Vec<String>,
+}
+
+impl MaterializedView {
+    pub fn revert_statement(&self) -> String {
+        format!("ALTER TABLE {} DELETE WHERE block_number IN ({{}}) SETTINGS mutations_sync = 2", self.target)
+    }
+
+    pub fn revert_range_statement(&self, from: u64, to: u64) -> String {
+        format!("ALTER TABLE {} DELETE WHERE block_number BETWEEN {} AND {} SETTINGS mutations_sync = 2", self.target, from, to)
+    }
+}
@@ -0,0 +1,142 @@
+use super::types::MaterializedView;
+
+const CANDLE_INTERVALS: [(&str, &str); 4] = [
+    ("1m", "toStartOfMinute"),
+    ("5m", "toStartOfFiveMinutes"),
+    ("1h", "toStartOfHour"),
+    ("1d", "toStartOfDay"),
+];
+
+pub fn definitions() -> Vec<MaterializedView> {
+    let mut views: Vec<MaterializedView> = CANDLE_INTERVALS
+        .iter()
+        .map(|(interval, bucket_fn)| candles(interval, bucket_fn))
+        .collect();
+
+    views.push(daily_sums(
+        "uni_v4_pool_fees_daily",
+        "uni_v4_fees",
+        &["pool_id"],
+        &["fee_amount0", "fee_amount1", "protocol_fee_amount0", "protocol_fee_amount1", "lp_fee_amount0", "lp_fee_amount1"],
+        "swaps",
+    ));
+    views.push(daily_sums(
+        "uni_v4_position_fees_daily",
+        "uni_v4_position_fees",
+        &["pool_id", "position_id"],
+        &["fee_amount0", "fee_amount1"],
+        "allocations",
+    ));
+    views
+}
+
+/// OHLCV candles per pool from `uni_v4_swaps`. Prices are the raw `(sqrtPriceX96 / 2^96)^2`
+/// (currency1 per currency0, not decimal-adjusted) and volumes are absolute raw amounts.
+/// Partial aggregates are kept per block in `uni_v4_candles_{interval}_state` and merged by
+/// the `uni_v4_candles_{interval}` view.
+fn candles(interval: &str, bucket_fn: &str) -> MaterializedView {
+    let name = format!("uni_v4_candles_{}", interval);
+    let target = format!("{}_state", name);
+    let price = "pow(toFloat64(sqrt_price_x96) / pow(2, 96), 2)";
+    let order = "(block_number, transaction_index, log_index)";
+
+    let create_target = format!(
+        "CREATE TABLE IF NOT EXISTS {target} (\n    \
+            pool_id FixedString(66),\n    \
+            bucket DateTime('UTC'),\n    \
+            block_number UInt64,\n    \
+            open AggregateFunction(argMin, Float64, Tuple(UInt64, UInt32, UInt32)),\n    \
+            high AggregateFunction(max, Float64),\n    \
+            low AggregateFunction(min, Float64),\n    \
+            close AggregateFunction(argMax, Float64, Tuple(UInt64, UInt32, UInt32)),\n    \
+            volume0 AggregateFunction(sum, Decimal(38, 0)),\n    \
+            volume1 AggregateFunction(sum, Decimal(38, 0)),\n    \
+            trades AggregateFunction(count)\n\
+        ) ENGINE = AggregatingMergeTree() PARTITION BY toYYYYMM(bucket) ORDER BY (pool_id, bucket, block_number)"
+    );
+
+    let create_view = format!(
+        "CREATE MATERIALIZED VIEW IF NOT EXISTS {name}_mv TO {target} AS\n\
+        SELECT\n    \
+            pool_id,\n    \
+            {bucket_fn}(block_timestamp) AS bucket,\n    \
+            block_number,\n    \
+            argMinState({price}, {order}) AS open,\n    \
+            maxState({price}) AS high,\n    \
+            minState({price}) AS low,\n    \
+            argMaxState({price}, {order}) AS close,\n    \
+            sumState(abs(amount0)) AS volume0,\n    \
+            sumState(abs(amount1)) AS volume1,\n    \
+            countState() AS trades\n\
+        FROM uni_v4_swaps\n\
+        GROUP BY pool_id, bucket, block_number"
+    );
+
+    let create_merged = format!(
+        "CREATE VIEW IF NOT EXISTS {name} AS\n\
+        SELECT\n    \
+            pool_id,\n    \
+            bucket,\n    \
+            argMinMerge(open) AS open,\n    \
+            maxMerge(high) AS high,\n    \
+            minMerge(low) AS low,\n    \
+            argMaxMerge(close) AS close,\n    \
+            sumMerge(volume0) AS volume0,\n    \
+            sumMerge(volume1) AS volume1,\n    \
+            countMerge(trades) AS trades\n\
+        FROM {target}\n\
+        GROUP BY pool_id, bucket"
+    );
+
+    MaterializedView {
+        source: "uni_v4_swaps",
+        target,
+        create_statements: vec![create_target, create_view, create_merged],
+    }
+}
+
+/// Per-day sums of the `Decimal(38, 0)` columns `sums` of `source`, grouped by `keys`, with
+/// the number of `source` rows summed as `count`.
+/// Partial sums are kept per block in `{name}_state` and merged by the `{name}` view.
+fn daily_sums(name: &str, source: &'static str, keys: &[&str], sums: &[&str], count: &str) -> MaterializedView {
+    let target = format!("{}_state", name);
+    let group_by = keys.join(", ");
+
+    let mut target_columns: Vec<String> = keys.iter().map(|key| format!("{} FixedString(66)", key)).collect();
+    target_columns.push("day Date".to_string());
+    target_columns.push("block_number UInt64".to_string());
+    target_columns.extend(sums.iter().map(|col| format!("{}_state AggregateFunction(sum, Decimal(38, 0))", col)));
+    target_columns.push(format!("{count}_state AggregateFunction(count)"));
+
+    let create_target = format!(
+        "CREATE TABLE IF NOT EXISTS {target} (\n    {}\n) \
+        ENGINE = AggregatingMergeTree() PARTITION BY toYYYYMM(day) ORDER BY ({group_by}, day, block_number)",
+        target_columns.join(",\n    ")
+    );
+
+    let mut state_columns: Vec<String> = sums.iter().map(|col| format!("sumState({col}) AS {col}_state")).collect();
+    state_columns.push(format!("countState() AS {count}_state"));
+    let create_view = format!(
+        "CREATE MATERIALIZED VIEW IF NOT EXISTS {name}_mv TO {target} AS\n\
+        SELECT {group_by}, toDate(block_timestamp) AS day, block_number, {}\n\
+        FROM {source}\n\
+        GROUP BY {group_by}, day, block_number",
+        state_columns.join(", ")
+    );
+
+    let mut merge_columns: Vec<String> = sums.iter().map(|col| format!("sumMerge({col}_state) AS {col}")).collect();
+    merge_columns.push(format!("countMerge({count}_state) AS {count}"));
+    let create_merged = format!(
+        "CREATE VIEW IF NOT EXISTS {name} AS\n\
+        SELECT {group_by}, day, {}\n\
+        FROM {target}\n\
+        GROUP BY {group_by}, day",
+        merge_columns.join(", ")
+    );
+
+    MaterializedView {
+        source,
+        target,
+        create_statements: vec![create_target, create_view, create_merged],
+    }
+}
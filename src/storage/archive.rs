@@ -0,0 +1,316 @@
+use crate::indexer::EthereumBlockData;
+use alloy::primitives::{Address, Bytes, Log, Signature, TxKind, B256, U256};
+use alloy_consensus::{BlockBody, EthereumTxEnvelope, Header, Signed, TxEip1559, TxType};
+use clickhouse::Client;
+use eyre::{bail, Result};
+use reth_primitives::{Block, Receipt, RecoveredBlock};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct RawLogRow {
+    block_number: u64,
+    block_timestamp: i64,
+    transaction_hash: String,
+    transaction_index: u32,
+    log_index: u32,
+    log_address: String,
+    topic0: String,
+    topic1: String,
+    topic2: String,
+    topic3: String,
+    data: String,
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct TransactionRow {
+    block_number: u64,
+    transaction_index: u32,
+    from_address: String,
+    to_address: String,
+    value: String,
+    nonce: u64,
+    input: String,
+    success: bool,
+    gas_limit: u64,
+    gas_used: u64,
+    max_fee_per_gas: String,
+    max_priority_fee_per_gas: String,
+    base_fee_per_gas: u64,
+    block_beneficiary: String,
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct ArchiveBounds {
+    first_block: u64,
+    last_block: u64,
+    logs: u64,
+}
+
+/// What the raw log archive holds for a block range.
+#[derive(Debug)]
+pub struct ArchiveCoverage {
+    pub blocks: u64,
+    pub logs: u64,
+}
+
+#[derive(Default)]
+struct ArchivedTransaction {
+    hash: B256,
+    logs: Vec<(u32, Log)>,
+    row: Option<TransactionRow>,
+}
+
+#[derive(Default)]
+struct ArchivedBlock {
+    timestamp: u64,
+    transactions: BTreeMap<u32, ArchivedTransaction>,
+}
+
+/// Checks that blocks `from..=to` can be rebuilt from the archive before `tables` are
+/// deleted for them: the archive must span the range, every archived transaction must have
+/// its `uni_v4_transactions` row, and `tables` must have no rows in blocks without archived
+/// logs, as those would be deleted and never rebuilt.
+pub async fn check_coverage(client: &Client, from: u64, to: u64, tables: &[&str]) -> Result<ArchiveCoverage> {
+    let bounds = client
+        .query("SELECT min(block_number) AS first_block, max(block_number) AS last_block, count() AS logs FROM uni_v4_raw_logs")
+        .fetch_one::<ArchiveBounds>()
+        .await?;
+    if bounds.logs == 0 { bail!("the raw log archive is empty"); }
+    if bounds.first_block > from || bounds.last_block < to {
+        bail!(
+            "the raw log archive covers blocks {}..={}, not {}..={}",
+            bounds.first_block, bounds.last_block, from, to
+        );
+    }
+
+    let missing = client
+        .query(
+            "SELECT count() FROM \
+             (SELECT DISTINCT block_number, transaction_index FROM uni_v4_raw_logs WHERE block_number BETWEEN ? AND ?) \
+             WHERE (block_number, transaction_index) NOT IN \
+             (SELECT block_number, transaction_index FROM uni_v4_transactions WHERE block_number BETWEEN ? AND ?)",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(from)
+        .bind(to)
+        .fetch_one::<u64>()
+        .await?;
+    if missing > 0 {
+        bail!("{} archived transactions in blocks {}..={} have no uni_v4_transactions row", missing, from, to);
+    }
+
+    for table in tables {
+        let orphaned = client
+            .query(&format!(
+                "SELECT uniqExact(block_number) FROM {} WHERE block_number BETWEEN ? AND ? AND block_number NOT IN \
+                 (SELECT DISTINCT block_number FROM uni_v4_raw_logs WHERE block_number BETWEEN ? AND ?)",
+                table
+            ))
+            .bind(from)
+            .bind(to)
+            .bind(from)
+            .bind(to)
+            .fetch_one::<u64>()
+            .await?;
+        if orphaned > 0 {
+            bail!("{} has rows in {} blocks of {}..={} without archived logs", table, orphaned, from, to);
+        }
+    }
+
+    let blocks = client
+        .query("SELECT uniqExact(block_number) FROM uni_v4_raw_logs WHERE block_number BETWEEN ? AND ?")
+        .bind(from)
+        .bind(to)
+        .fetch_one::<u64>()
+        .await?;
+    let logs = client
+        .query("SELECT count() FROM uni_v4_raw_logs WHERE block_number BETWEEN ? AND ?")
+        .bind(from)
+        .bind(to)
+        .fetch_one::<u64>()
+        .await?;
+    Ok(ArchiveCoverage { blocks, logs })
+}
+
+/// Rebuilds blocks `from..=to` from `uni_v4_raw_logs` and `uni_v4_transactions`, for replaying
+/// through processors without the node. Only blocks with archived PoolManager logs are
+/// returned. Transaction and log indexes match the original block: transactions without a
+/// PoolManager log are empty placeholders and receipt logs not emitted by the PoolManager are
+/// empty logs from the zero address. Archived transactions keep their hash, signer, `to`,
+/// value, nonce, gas and fee fields and the method id as input; signatures are not kept.
+pub async fn archived_blocks(client: &Client, from: u64, to: u64) -> Result<Vec<EthereumBlockData>> {
+    let logs = client
+        .query(
+            "SELECT block_number, toInt64(toUnixTimestamp(block_timestamp)) AS block_timestamp, transaction_hash, \
+             transaction_index, log_index, log_address, topic0, topic1, topic2, topic3, data \
+             FROM uni_v4_raw_logs WHERE block_number BETWEEN ? AND ? \
+             ORDER BY block_number, transaction_index, log_index",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all::<RawLogRow>()
+        .await?;
+    if logs.is_empty() { return Ok(Vec::new()); }
+
+    let transactions = client
+        .query(
+            "SELECT block_number, transaction_index, from_address, to_address, toString(value) AS value, nonce, \
+             method_id AS input, success, gas_limit, gas_used, toString(max_fee_per_gas) AS max_fee_per_gas, \
+             toString(max_priority_fee_per_gas) AS max_priority_fee_per_gas, base_fee_per_gas, block_beneficiary \
+             FROM uni_v4_transactions WHERE block_number BETWEEN ? AND ? \
+             LIMIT 1 BY block_number, transaction_index",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all::<TransactionRow>()
+        .await?;
+
+    let mut blocks: BTreeMap<u64, ArchivedBlock> = BTreeMap::new();
+    for row in logs {
+        let block = blocks.entry(row.block_number).or_default();
+        block.timestamp = row.block_timestamp.max(0) as u64;
+
+        let topics: Vec<B256> = [&row.topic0, &row.topic1, &row.topic2, &row.topic3]
+            .into_iter()
+            .take_while(|topic| !topic.is_empty())
+            .filter_map(|topic| topic.parse().ok())
+            .collect();
+        let data: Bytes = row.data.parse().unwrap_or_default();
+        let address: Address = row.log_address.parse().unwrap_or_default();
+
+        let transaction = block.transactions.entry(row.transaction_index).or_default();
+        transaction.hash = row.transaction_hash.parse().unwrap_or_default();
+        transaction.logs.push((row.log_index, Log::new_unchecked(address, topics, data)));
+    }
+    for row in transactions {
+        let Some(block) = blocks.get_mut(&row.block_number) else { continue; };
+        let Some(transaction) = block.transactions.get_mut(&row.transaction_index) else { continue; };
+        transaction.row = Some(row);
+    }
+
+    Ok(blocks.into_iter().map(|(number, block)| rebuild_block(number, block)).collect())
+}
+
+fn rebuild_block(number: u64, block: ArchivedBlock) -> EthereumBlockData {
+    let transaction_count = block.transactions.keys().next_back().map_or(0, |idx| *idx as usize + 1);
+    let first_row = block.transactions.values().find_map(|tx| tx.row.as_ref());
+
+    let header = Header {
+        number,
+        timestamp: block.timestamp,
+        base_fee_per_gas: first_row.map(|row| row.base_fee_per_gas),
+        beneficiary: first_row.and_then(|row| row.block_beneficiary.parse().ok()).unwrap_or_default(),
+        ..Default::default()
+    };
+
+    let mut archived = block.transactions;
+    let mut transactions = Vec::with_capacity(transaction_count);
+    let mut senders = Vec::with_capacity(transaction_count);
+    let mut receipts = Vec::with_capacity(transaction_count);
+    let mut cumulative_gas_used = 0u64;
+
+    for tx_idx in 0..transaction_count as u32 {
+        let transaction = archived.remove(&tx_idx).unwrap_or_default();
+
+        let mut logs: Vec<Log> = Vec::new();
+        for (log_idx, log) in transaction.logs {
+            if (log_idx as usize) < logs.len() { continue; }
+            logs.resize_with(log_idx as usize, || Log::new_unchecked(Address::ZERO, Vec::new(), Bytes::new()));
+            logs.push(log);
+        }
+
+        let (tx, sender, success) = match &transaction.row {
+            Some(row) => {
+                let tx = TxEip1559 {
+                    chain_id: 1,
+                    nonce: row.nonce,
+                    gas_limit: row.gas_limit,
+                    max_fee_per_gas: row.max_fee_per_gas.parse().unwrap_or_default(),
+                    max_priority_fee_per_gas: row.max_priority_fee_per_gas.parse().unwrap_or_default(),
+                    to: row.to_address.parse::<Address>().ok().filter(|to| !to.is_zero()).map_or(TxKind::Create, TxKind::Call),
+                    value: row.value.parse().unwrap_or_default(),
+                    input: row.input.parse().unwrap_or_default(),
+                    ..Default::default()
+                };
+                cumulative_gas_used += row.gas_used;
+                (tx, row.from_address.parse().unwrap_or_default(), row.success)
+            }
+            None => (TxEip1559::default(), Address::ZERO, !logs.is_empty()),
+        };
+
+        let signature = Signature::new(U256::ZERO, U256::ZERO, false);
+        transactions.push(EthereumTxEnvelope::Eip1559(Signed::new_unchecked(tx, signature, transaction.hash)));
+        senders.push(sender);
+        receipts.push(Receipt { tx_type: TxType::Eip1559, success, cumulative_gas_used, logs });
+    }
+
+    let body = BlockBody { transactions, ommers: Vec::new(), withdrawals: None };
+    (RecoveredBlock::new_unhashed(Block { header, body }, senders), receipts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(transaction_index: u32, from_address: Address, gas_used: u64) -> TransactionRow {
+        TransactionRow {
+            block_number: 100,
+            transaction_index,
+            from_address: from_address.to_string(),
+            to_address: Address::repeat_byte(0xee).to_string(),
+            value: "0".to_string(),
+            nonce: 7,
+            input: "0x".to_string(),
+            success: true,
+            gas_limit: 100_000,
+            gas_used,
+            max_fee_per_gas: "2000000000".to_string(),
+            max_priority_fee_per_gas: "1000000000".to_string(),
+            base_fee_per_gas: 1_000_000_000,
+            block_beneficiary: Address::repeat_byte(0xbb).to_string(),
+        }
+    }
+
+    fn log(address: Address) -> Log {
+        Log::new_unchecked(address, vec![B256::repeat_byte(1)], Bytes::new())
+    }
+
+    #[test]
+    fn rebuild_block_keeps_transaction_and_log_indexes() {
+        let (pool_manager, sender1, sender3) = (Address::repeat_byte(4), Address::repeat_byte(1), Address::repeat_byte(3));
+        let mut block = ArchivedBlock { timestamp: 1_700_000_000, ..Default::default() };
+        block.transactions.insert(1, ArchivedTransaction {
+            hash: B256::repeat_byte(0x11),
+            logs: vec![(2, log(pool_manager)), (3, log(pool_manager))],
+            row: Some(row(1, sender1, 21_000)),
+        });
+        block.transactions.insert(3, ArchivedTransaction {
+            hash: B256::repeat_byte(0x33),
+            logs: vec![(0, log(pool_manager))],
+            row: Some(row(3, sender3, 50_000)),
+        });
+
+        let (rebuilt, receipts) = rebuild_block(100, block);
+        assert_eq!(rebuilt.num_hash().number, 100);
+        assert_eq!(rebuilt.timestamp, 1_700_000_000);
+        assert_eq!(rebuilt.beneficiary, Address::repeat_byte(0xbb));
+
+        assert_eq!(rebuilt.body().transactions.len(), 4);
+        assert_eq!(rebuilt.senders(), &[Address::ZERO, sender1, Address::ZERO, sender3]);
+        assert_eq!(*rebuilt.body().transactions[1].hash(), B256::repeat_byte(0x11));
+        assert_eq!(*rebuilt.body().transactions[3].hash(), B256::repeat_byte(0x33));
+
+        let log_counts: Vec<usize> = receipts.iter().map(|r| r.logs.len()).collect();
+        assert_eq!(log_counts, vec![0, 4, 0, 1]);
+        assert_eq!(receipts[1].logs[0].address, Address::ZERO);
+        assert_eq!(receipts[1].logs[2].address, pool_manager);
+        assert_eq!(receipts[1].logs[3].address, pool_manager);
+        assert_eq!(receipts[3].logs[0].address, pool_manager);
+
+        let cumulative: Vec<u64> = receipts.iter().map(|r| r.cumulative_gas_used).collect();
+        assert_eq!(cumulative, vec![0, 21_000, 21_000, 71_000]);
+        assert_eq!(receipts.iter().map(|r| r.success).collect::<Vec<_>>(), vec![false, true, false, true]);
+    }
+}
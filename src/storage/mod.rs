@@ -1,6 +1,8 @@
+pub mod archive;
+pub mod reader;
 pub mod writer;
 use clickhouse::Client;
-use crate::schema::TABLES;
+use crate::schema::{TABLES, VIEWS};
 use reth_tracing::tracing::info;
 
 pub async fn init_tables(client: &Client) -> eyre::Result<()> {
@@ -8,12 +10,22 @@ pub async fn init_tables(client: &Client) -> eyre::Result<()> {
         let create_table_sql = table.create_table_sql();
         client.query(&create_table_sql).execute().await?;
 
+        for add_column_sql in table.add_column_statements() {
+            client.query(&add_column_sql).execute().await?;
+        }
+
         let index_statements = table.create_index_statements();
         for index_sql in index_statements {
             client.query(&index_sql).execute().await?;
         }
     }
 
+    for view in VIEWS.iter() {
+        for create_sql in &view.create_statements {
+            client.query(create_sql).execute().await?;
+        }
+    }
+
     info!("Initialized database tables");
     Ok(())
 }
@@ -0,0 +1,250 @@
+use alloy::primitives::{Address, FixedBytes};
+use clickhouse::Client;
+use eyre::Result;
+use serde::Deserialize;
+use std::{collections::{HashMap, HashSet}, sync::Mutex};
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct PoolCurrenciesRow {
+    pool_id: String,
+    currency0: String,
+    currency1: String,
+}
+
+lazy_static::lazy_static! {
+    static ref POOL_CURRENCIES: Mutex<HashMap<FixedBytes<32>, (Address, Address)>> = Mutex::new(HashMap::new());
+}
+
+/// Records the currencies of a pool decoded from an `Initialize` event, so pools created in
+/// the block being processed resolve before their `uni_v4_pools` row is written.
+pub fn remember_pool_currencies(pool_id: FixedBytes<32>, currency0: Address, currency1: Address) {
+    POOL_CURRENCIES.lock().unwrap().insert(pool_id, (currency0, currency1));
+}
+
+/// Currencies of the given pools, from the in-process cache or `uni_v4_pools`.
+pub async fn pool_currencies(
+    client: &Client,
+    pool_ids: &[FixedBytes<32>],
+) -> Result<HashMap<FixedBytes<32>, (Address, Address)>> {
+    let mut resolved = HashMap::with_capacity(pool_ids.len());
+    let mut missing: Vec<String> = Vec::new();
+    {
+        let cache = POOL_CURRENCIES.lock().unwrap();
+        for pool_id in pool_ids {
+            match cache.get(pool_id) {
+                Some(currencies) => { resolved.insert(*pool_id, *currencies); }
+                None => missing.push(pool_id.to_string()),
+            }
+        }
+    }
+    missing.sort_unstable();
+    missing.dedup();
+    if missing.is_empty() { return Ok(resolved); }
+
+    let rows = client
+        .query(
+            "SELECT toString(pool_id) AS pool_id, currency0, currency1 FROM uni_v4_pools \
+             WHERE has(?, toString(pool_id)) LIMIT 1 BY pool_id",
+        )
+        .bind(missing)
+        .fetch_all::<PoolCurrenciesRow>()
+        .await?;
+
+    let mut cache = POOL_CURRENCIES.lock().unwrap();
+    for row in rows {
+        let (Ok(pool_id), Ok(currency0), Ok(currency1)) =
+            (row.pool_id.parse(), row.currency0.parse(), row.currency1.parse()) else { continue; };
+        cache.insert(pool_id, (currency0, currency1));
+        resolved.insert(pool_id, (currency0, currency1));
+    }
+    Ok(resolved)
+}
+
+/// Latest row per key of `table` before `block_number`, i.e. the state a processor carrying
+/// state across blocks starts the block from. `columns` is the select list and `keys` pairs
+/// each key column with the values to load; rows are taken in chain order, one per key.
+pub async fn latest_rows_before<T>(
+    client: &Client,
+    table: &str,
+    columns: &str,
+    keys: &[(&str, Vec<String>)],
+    block_number: u64,
+) -> Result<Vec<T>>
+where
+    T: clickhouse::Row + for<'b> Deserialize<'b>,
+{
+    let filters: Vec<String> = keys.iter().map(|(column, _)| format!("has(?, toString({}))", column)).collect();
+    let limit_by: Vec<&str> = keys.iter().map(|(column, _)| *column).collect();
+
+    let mut query = client.query(&format!(
+        "SELECT {} FROM {} WHERE {} AND block_number < ? \
+         ORDER BY block_number DESC, transaction_index DESC, log_index DESC \
+         LIMIT 1 BY {}",
+        columns,
+        table,
+        filters.join(" AND "),
+        limit_by.join(", ")
+    ));
+    for (_, values) in keys {
+        let mut values = values.clone();
+        values.sort_unstable();
+        values.dedup();
+        query = query.bind(values);
+    }
+    Ok(query.bind(block_number).fetch_all::<T>().await?)
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct RegisteredPoolRow {
+    pool_address: String,
+    fee: u32,
+}
+
+lazy_static::lazy_static! {
+    static ref REGISTERED_POOLS: Mutex<HashMap<(&'static str, Address), Option<u32>>> = Mutex::new(HashMap::new());
+}
+
+/// Records a pool created in the block being processed in the `registry` table's cache.
+pub fn remember_registered_pool(registry: &'static str, pool: Address, fee: u32) {
+    REGISTERED_POOLS.lock().unwrap().insert((registry, pool), Some(fee));
+}
+
+/// Records an address verified not to be a pool of the `registry` table's factory.
+pub fn remember_unregistered_pool(registry: &'static str, address: Address) {
+    REGISTERED_POOLS.lock().unwrap().insert((registry, address), None);
+}
+
+/// Which of `addresses` are pools of a v2/v3 factory, from the in-process cache or the
+/// `registry` pools table (`uni_v2_pools`, `uni_v3_pools`), with their fee in pips, and the
+/// addresses found in neither. Those may be pools created before the table was indexed, so
+/// they are left to the caller to verify and are not cached.
+pub async fn registered_pools(
+    client: &Client,
+    registry: &'static str,
+    addresses: &[Address],
+) -> Result<(HashMap<Address, u32>, Vec<Address>)> {
+    let mut resolved = HashMap::with_capacity(addresses.len());
+    let mut missing: Vec<Address> = Vec::new();
+    {
+        let cache = REGISTERED_POOLS.lock().unwrap();
+        for address in addresses {
+            match cache.get(&(registry, *address)) {
+                Some(Some(fee)) => { resolved.insert(*address, *fee); }
+                Some(None) => {}
+                None => missing.push(*address),
+            }
+        }
+    }
+    missing.sort_unstable();
+    missing.dedup();
+    if missing.is_empty() { return Ok((resolved, missing)); }
+
+    let rows = client
+        .query(&format!(
+            "SELECT pool_address, fee FROM {} WHERE has(?, pool_address) LIMIT 1 BY pool_address",
+            registry
+        ))
+        .bind(missing.iter().map(|address| address.to_checksum(Some(1))).collect::<Vec<_>>())
+        .fetch_all::<RegisteredPoolRow>()
+        .await?;
+
+    let mut cache = REGISTERED_POOLS.lock().unwrap();
+    for row in rows {
+        let Ok(pool) = row.pool_address.parse::<Address>() else { continue; };
+        cache.insert((registry, pool), Some(row.fee));
+        resolved.insert(pool, row.fee);
+    }
+    missing.retain(|address| !resolved.contains_key(address));
+    Ok((resolved, missing))
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct AbiContractRow {
+    contract_address: String,
+}
+
+lazy_static::lazy_static! {
+    static ref ABI_CONTRACTS: Mutex<HashMap<(&'static str, Address), bool>> = Mutex::new(HashMap::new());
+}
+
+/// Records a contract announced by an ABI source's factory in the block being processed.
+pub fn remember_abi_contract(source: &'static str, contract: Address) {
+    ABI_CONTRACTS.lock().unwrap().insert((source, contract), true);
+}
+
+/// Which of `addresses` were discovered through the factory of the ABI `source`, from the
+/// in-process cache or `abi_contracts`. Unknown addresses are cached as such, as with
+/// `registered_pools`.
+pub async fn abi_contracts(
+    client: &Client,
+    source: &'static str,
+    addresses: &[Address],
+) -> Result<HashSet<Address>> {
+    let mut resolved = HashSet::with_capacity(addresses.len());
+    let mut missing: Vec<Address> = Vec::new();
+    {
+        let cache = ABI_CONTRACTS.lock().unwrap();
+        for address in addresses {
+            match cache.get(&(source, *address)) {
+                Some(true) => { resolved.insert(*address); }
+                Some(false) => {}
+                None => missing.push(*address),
+            }
+        }
+    }
+    missing.sort_unstable();
+    missing.dedup();
+    if missing.is_empty() { return Ok(resolved); }
+
+    let rows = client
+        .query(
+            "SELECT contract_address FROM abi_contracts \
+             WHERE source = ? AND has(?, contract_address) LIMIT 1 BY contract_address",
+        )
+        .bind(source)
+        .bind(missing.iter().map(|address| address.to_checksum(Some(1))).collect::<Vec<_>>())
+        .fetch_all::<AbiContractRow>()
+        .await?;
+
+    let mut cache = ABI_CONTRACTS.lock().unwrap();
+    for row in rows {
+        let Ok(contract) = row.contract_address.parse::<Address>() else { continue; };
+        resolved.insert(contract);
+    }
+    for address in missing {
+        cache.insert((source, address), resolved.contains(&address));
+    }
+    Ok(resolved)
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct HookAddressRow {
+    hook_address: String,
+}
+
+lazy_static::lazy_static! {
+    static ref HOOK_ADDRESSES: Mutex<Option<HashSet<Address>>> = Mutex::new(None);
+}
+
+/// Adds a hook from an `Initialize` event in the block being processed to the watch-set
+/// loaded by `hook_addresses`.
+pub fn remember_hook(hook: Address) {
+    if let Some(hooks) = HOOK_ADDRESSES.lock().unwrap().as_mut() { hooks.insert(hook); }
+}
+
+/// Every hook address a pool was initialized with, loaded once from `uni_v4_hooks` and kept
+/// up to date through `remember_hook`.
+pub async fn hook_addresses(client: &Client) -> Result<HashSet<Address>> {
+    if let Some(hooks) = HOOK_ADDRESSES.lock().unwrap().as_ref() { return Ok(hooks.clone()); }
+
+    let rows = client
+        .query("SELECT DISTINCT hook_address FROM uni_v4_hooks")
+        .fetch_all::<HookAddressRow>()
+        .await?;
+    let loaded: HashSet<Address> = rows.iter().filter_map(|row| row.hook_address.parse().ok()).collect();
+
+    let mut cache = HOOK_ADDRESSES.lock().unwrap();
+    let hooks = cache.get_or_insert_with(HashSet::new);
+    hooks.extend(loaded);
+    Ok(hooks.clone())
+}
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use clickhouse::Client;
 use eyre::Result;
-use crate::schema::Table;
+use crate::schema::{views_for, Table};
 
 pub struct ClickhouseWriter {
     client: Arc<Client>,
@@ -14,6 +14,9 @@ impl ClickhouseWriter {
         Ok(Self { client: Arc::clone(client), table, records: Vec::with_capacity(1024) })
     }
 
+    #[inline]
+    pub fn table_name(&self) -> &'static str { self.table.name }
+
     #[inline]
     pub fn write_record(&mut self, record: Vec<String>) {
         self.records.push(record);
@@ -36,8 +39,28 @@ impl ClickhouseWriter {
         }
         let delete_stmt = self.table.revert_statement().replace("{}", &block_list);
         self.client.query(&delete_stmt).execute().await?;
+
+        for view in views_for(self.table.name) {
+            let delete_stmt = view.revert_statement().replace("{}", &block_list);
+            self.client.query(&delete_stmt).execute().await?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the rows of blocks `from..=to`, and those of the views fed by the table, once
+    /// the deletes have finished.
+    pub async fn revert_range(&self, from: u64, to: u64) -> Result<()> {
+        self.client.query(&self.table.revert_range_statement(from, to)).execute().await?;
+        for view in views_for(self.table.name) {
+            self.client.query(&view.revert_range_statement(from, to)).execute().await?;
+        }
         Ok(())
     }
+
+    /// Rows of the table in blocks `from..=to`, i.e. what `revert_range` would delete.
+    pub async fn count_range(&self, from: u64, to: u64) -> Result<u64> {
+        Ok(self.client.query(&self.table.count_range_statement(from, to)).fetch_one::<u64>().await?)
+    }
 }
 
 pub trait IntoClickhouseValue { fn into_ch_value(&self) -> String; }
@@ -48,6 +71,7 @@ impl IntoClickhouseValue for i64 { #[inline] fn into_ch_value(&self) -> String {
 impl IntoClickhouseValue for i32 { #[inline] fn into_ch_value(&self) -> String { self.to_string() } }
 impl IntoClickhouseValue for i128 { #[inline] fn into_ch_value(&self) -> String { self.to_string() } }
 impl IntoClickhouseValue for u128 { #[inline] fn into_ch_value(&self) -> String { self.to_string() } }
+impl IntoClickhouseValue for f64 { #[inline] fn into_ch_value(&self) -> String { self.to_string() } }
 impl IntoClickhouseValue for bool { #[inline] fn into_ch_value(&self) -> String { if *self { "1" } else { "0" }.to_string() } }
 impl IntoClickhouseValue for chrono::DateTime<chrono::Utc> { #[inline] fn into_ch_value(&self) -> String { self.format("%Y-%m-%d %H:%M:%S").to_string() } }
 impl IntoClickhouseValue for primitive_types::H256 { #[inline] fn into_ch_value(&self) -> String { self.to_string() } }
@@ -57,6 +81,7 @@ impl IntoClickhouseValue for alloy::primitives::FixedBytes<32> { #[inline] fn in
 impl<const BITS: usize, const LIMBS: usize> IntoClickhouseValue for alloy::primitives::Uint<BITS, LIMBS> { #[inline] fn into_ch_value(&self) -> String { self.to_string() } }
 impl<const BITS: usize, const LIMBS: usize> IntoClickhouseValue for alloy::primitives::Signed<BITS, LIMBS> { #[inline] fn into_ch_value(&self) -> String { self.to_string() } }
 impl<T: IntoClickhouseValue + ?Sized> IntoClickhouseValue for &T { #[inline] fn into_ch_value(&self) -> String { (*self).into_ch_value() } }
+impl<T: IntoClickhouseValue> IntoClickhouseValue for Option<T> { #[inline] fn into_ch_value(&self) -> String { self.as_ref().map_or_else(|| "\\N".to_string(), |value| value.into_ch_value()) } }
 impl IntoClickhouseValue for Vec<u8> { #[inline] fn into_ch_value(&self) -> String { alloy::primitives::hex::encode_prefixed(self) } }
 impl IntoClickhouseValue for [u8] { #[inline] fn into_ch_value(&self) -> String { alloy::primitives::hex::encode_prefixed(self) } }
 
@@ -0,0 +1,72 @@
+use alloy::primitives::{Address, Bytes};
+use alloy_rpc_types_trace::parity::{Action, CallAction, TraceOutput, TraceResultsWithTransactionHash, TransactionTrace};
+
+/// A call frame of a parity-style transaction trace.
+#[derive(Clone, Copy)]
+pub struct CallFrame<'a> {
+    pub trace: &'a TransactionTrace,
+    pub action: &'a CallAction,
+}
+
+impl<'a> CallFrame<'a> {
+    pub fn from(&self) -> Address { self.action.from }
+
+    pub fn to(&self) -> Address { self.action.to }
+
+    pub fn input(&self) -> &'a Bytes { &self.action.input }
+
+    pub fn selector(&self) -> Option<[u8; 4]> {
+        self.action.input.get(..4).map(|s| [s[0], s[1], s[2], s[3]])
+    }
+
+    pub fn output(&self) -> Option<&'a Bytes> {
+        match &self.trace.result {
+            Some(TraceOutput::Call(output)) => Some(&output.output),
+            _ => None,
+        }
+    }
+
+    pub fn gas_used(&self) -> u64 {
+        match &self.trace.result {
+            Some(TraceOutput::Call(output)) => output.gas_used.to(),
+            _ => 0,
+        }
+    }
+
+    pub fn success(&self) -> bool { self.trace.error.is_none() }
+
+    /// Whether `other` was called, directly or not, from within this frame.
+    pub fn is_ancestor_of(&self, other: &CallFrame<'_>) -> bool {
+        let (own, other) = (&self.trace.trace_address, &other.trace.trace_address);
+        own.len() < other.len() && other.starts_with(own)
+    }
+
+    pub fn trace_address(&self) -> String {
+        self.trace.trace_address.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(".")
+    }
+}
+
+/// Iterates over the call frames of a transaction trace in execution order.
+pub fn calls(trace: &TraceResultsWithTransactionHash) -> impl Iterator<Item = CallFrame<'_>> {
+    trace.full_trace.trace.iter().filter_map(|trace| match &trace.action {
+        Action::Call(action) => Some(CallFrame { trace, action }),
+        _ => None,
+    })
+}
+
+/// For each successful call to `to` with the given selector, in execution order, the frames
+/// it was called from, outermost first. Calls made from a frame that reverted later are
+/// left out, as their effects and logs were rolled back with it.
+pub fn call_paths<'a>(
+    trace: &'a TraceResultsWithTransactionHash,
+    to: Address,
+    selector: [u8; 4],
+) -> Vec<Vec<CallFrame<'a>>> {
+    let frames: Vec<CallFrame<'a>> = calls(trace).collect();
+    frames
+        .iter()
+        .filter(|f| f.to() == to && f.success() && f.selector() == Some(selector))
+        .map(|call| frames.iter().filter(|f| f.is_ancestor_of(call)).cloned().collect::<Vec<_>>())
+        .filter(|path| path.iter().all(CallFrame::success))
+        .collect()
+}
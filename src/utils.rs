@@ -1,5 +1,6 @@
 use std::env;
 use clickhouse::Client;
+use alloy::primitives::{keccak256, Address, FixedBytes, U256};
 
 pub async fn connect_to_clickhouse() -> eyre::Result<Client> {
     let database_url = env::var("CLICKHOUSE_URL").unwrap_or_else(|_| "http://localhost:8123".to_string());
@@ -12,3 +13,126 @@ pub async fn connect_to_clickhouse() -> eyre::Result<Client> {
     
     Ok(client)
 }
+
+/// Position key as computed by `Position.calculatePositionKey` in the PoolManager:
+/// `keccak256(abi.encodePacked(owner, tickLower, tickUpper, salt))`.
+pub fn position_key(owner: Address, tick_lower: i32, tick_upper: i32, salt: FixedBytes<32>) -> FixedBytes<32> {
+    let mut packed = [0u8; 58];
+    packed[..20].copy_from_slice(owner.as_slice());
+    packed[20..23].copy_from_slice(&tick_lower.to_be_bytes()[1..]);
+    packed[23..26].copy_from_slice(&tick_upper.to_be_bytes()[1..]);
+    packed[26..].copy_from_slice(salt.as_slice());
+    keccak256(packed)
+}
+
+/// ERC-6909 ids of PoolManager claims are `uint256(uint160(currency))`.
+pub fn currency_of(id: U256) -> Address {
+    Address::from_word(FixedBytes::<32>::from(id.to_be_bytes::<32>()))
+}
+
+/// Price of currency0 denominated in currency1 from a pool's `sqrtPriceX96`, adjusted for
+/// both tokens' decimals.
+pub fn sqrt_price_x96_to_price(sqrt_price_x96: f64, decimals0: u32, decimals1: u32) -> f64 {
+    let ratio = sqrt_price_x96 / 2f64.powi(96);
+    ratio * ratio * 10f64.powi(decimals0 as i32 - decimals1 as i32)
+}
+
+/// `LPFeeLibrary.DYNAMIC_FEE_FLAG`: the `fee` a dynamic-fee pool is initialized with.
+pub const DYNAMIC_FEE_FLAG: u32 = 0x800000;
+
+/// Hook permissions encoded in the low 14 bits of a v4 hook address (see `Hooks.sol`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HookPermissions {
+    pub before_initialize: bool,
+    pub after_initialize: bool,
+    pub before_add_liquidity: bool,
+    pub after_add_liquidity: bool,
+    pub before_remove_liquidity: bool,
+    pub after_remove_liquidity: bool,
+    pub before_swap: bool,
+    pub after_swap: bool,
+    pub before_donate: bool,
+    pub after_donate: bool,
+    pub before_swap_return_delta: bool,
+    pub after_swap_return_delta: bool,
+    pub after_add_liquidity_return_delta: bool,
+    pub after_remove_liquidity_return_delta: bool,
+}
+
+impl HookPermissions {
+    pub const ALL_HOOK_MASK: u16 = (1 << 14) - 1;
+
+    pub fn bits(hooks: Address) -> u16 {
+        u16::from_be_bytes([hooks[18], hooks[19]]) & Self::ALL_HOOK_MASK
+    }
+
+    pub fn from_address(hooks: Address) -> Self {
+        let bits = Self::bits(hooks);
+        let flag = |shift: u16| bits & (1 << shift) != 0;
+        Self {
+            before_initialize: flag(13),
+            after_initialize: flag(12),
+            before_add_liquidity: flag(11),
+            after_add_liquidity: flag(10),
+            before_remove_liquidity: flag(9),
+            after_remove_liquidity: flag(8),
+            before_swap: flag(7),
+            after_swap: flag(6),
+            before_donate: flag(5),
+            after_donate: flag(4),
+            before_swap_return_delta: flag(3),
+            after_swap_return_delta: flag(2),
+            after_add_liquidity_return_delta: flag(1),
+            after_remove_liquidity_return_delta: flag(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{address, b256};
+
+    #[test]
+    fn position_key_matches_pool_manager() {
+        let position_manager = address!("0xbD216513d74C8cf14cf4747E6AaA6420FF64ee9e");
+        assert_eq!(
+            position_key(position_manager, -887220, 887220, FixedBytes::from(U256::from(1).to_be_bytes::<32>())),
+            b256!("0x5ba0028116215f3b8b83cd473ec37195a5773b218e065adc58cbb3f2430471b7")
+        );
+        assert_eq!(
+            position_key(position_manager, -60, 120, FixedBytes::ZERO),
+            b256!("0x203a6933f47d248d82fa68b0859e9377f296cf1e8fae3c5d369190e790582478")
+        );
+    }
+
+    #[test]
+    fn hook_permissions_decode_low_address_bits() {
+        // Ends in 0x68cc: the bit above the 14 flag bits is ignored.
+        let hooks = address!("0xd60d6b218116cfd801e28f78d011a203d2b068cc");
+        assert_eq!(HookPermissions::bits(hooks), 0x28cc);
+        assert_eq!(
+            HookPermissions::from_address(hooks),
+            HookPermissions {
+                before_initialize: true,
+                before_add_liquidity: true,
+                before_swap: true,
+                after_swap: true,
+                before_swap_return_delta: true,
+                after_swap_return_delta: true,
+                ..Default::default()
+            }
+        );
+
+        assert_eq!(HookPermissions::from_address(Address::ZERO), HookPermissions::default());
+        let all = HookPermissions::from_address(address!("0x0000000000000000000000000000000000003fff"));
+        assert!(all.before_initialize && all.after_remove_liquidity_return_delta && all.before_donate);
+    }
+
+    #[test]
+    fn currency_of_reads_the_low_160_bits() {
+        let usdc = address!("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+        assert_eq!(currency_of(U256::from_be_slice(usdc.as_slice())), usdc);
+        assert_eq!(currency_of(U256::ZERO), Address::ZERO);
+    }
+}